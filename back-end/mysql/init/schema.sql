@@ -2,25 +2,89 @@ DROP DATABASE IF EXISTS `todo`;
 CREATE DATABASE `todo`;
 USE `todo`;
 
-CREATE TABLE IF NOT EXISTS `tags` (
+CREATE TABLE IF NOT EXISTS `users` (
   `id` VARBINARY(16) NOT NULL,
-  `name` VARCHAR(255) NOT NULL,
+  `username` VARCHAR(255) UNIQUE,
+  `display_name` VARCHAR(255) NOT NULL,
+  `hashed_password` VARBINARY(60) NOT NULL,
   `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
   `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  `deleted_at` DATETIME,
+  -- done tasks untouched for this many days are archived, never when `NULL`
+  `auto_archive_days` INT,
   PRIMARY KEY (`id`)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
-CREATE TABLE IF NOT EXISTS `users` (
+CREATE TABLE IF NOT EXISTS `workspaces` (
   `id` VARBINARY(16) NOT NULL,
-  `username` VARCHAR(255) UNIQUE,
-  `display_name` VARCHAR(255) NOT NULL,
-  `hashed_password` VARBINARY(60) NOT NULL,
+  `name` VARCHAR(255) NOT NULL,
   `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
   `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
-  `deleted_at` DATETIME,
   PRIMARY KEY (`id`)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+CREATE TABLE IF NOT EXISTS `workspace_members` (
+  `workspace_id` VARBINARY(16) NOT NULL,
+  `user_id` VARBINARY(16) NOT NULL,
+  -- `owner`, `admin` or `member`
+  `role` VARCHAR(255) NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`workspace_id`, `user_id`),
+  INDEX (`user_id`),
+  FOREIGN KEY (`workspace_id`) REFERENCES `workspaces` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `workspace_invitations` (
+  `token` VARCHAR(64) NOT NULL,
+  `workspace_id` VARBINARY(16) NOT NULL,
+  `inviter_id` VARBINARY(16) NOT NULL,
+  -- given to whoever accepts the invitation
+  `role` VARCHAR(255) NOT NULL,
+  `expires_at` DATETIME NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`token`),
+  INDEX (`workspace_id`),
+  FOREIGN KEY (`workspace_id`) REFERENCES `workspaces` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`inviter_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `tags` (
+  `id` VARBINARY(16) NOT NULL,
+  `author_id` VARBINARY(16) NOT NULL,
+  -- shared by the members of the workspace, personal when unset
+  `workspace_id` VARBINARY(16),
+  `name` VARCHAR(255) NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  -- the author of a personal tag, unset for the tags of a workspace
+  `personal_author_id` VARBINARY(16) AS (IF(`workspace_id` IS NULL, `author_id`, NULL)),
+  PRIMARY KEY (`id`),
+  -- names are unique among the personal tags of the author and among the tags of a workspace
+  INDEX (`author_id`, `name`),
+  UNIQUE (`personal_author_id`, `name`),
+  UNIQUE (`workspace_id`, `name`),
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`workspace_id`) REFERENCES `workspaces` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `projects` (
+  `id` VARBINARY(16) NOT NULL,
+  `author_id` VARBINARY(16) NOT NULL,
+  `name` VARCHAR(255) NOT NULL,
+  `description` TEXT NOT NULL,
+  -- `#rrggbb`
+  `color` VARCHAR(7),
+  `icon` VARCHAR(255),
+  -- hidden from the project list by default since then
+  `archived_at` DATETIME,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  UNIQUE (`author_id`, `name`),
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE IF NOT EXISTS `state_mapping` (
   `state_id` TINYINT NOT NULL,
   `state_name` varchar(255) NOT NULL UNIQUE,
@@ -47,17 +111,38 @@ ON DUPLICATE KEY UPDATE priority_id=priority_id;
 CREATE TABLE IF NOT EXISTS `todos` (
   `id` VARBINARY(16) NOT NULL,
   `author_id` VARBINARY(16),
+  -- the one doing the task, who may differ from its author
+  `assignee_id` VARBINARY(16),
+  `parent_id` VARBINARY(16),
+  -- in the inbox when unset
+  `project_id` VARBINARY(16),
+  -- seen by the members of the workspace, personal when unset
+  `workspace_id` VARBINARY(16),
   `title` VARCHAR(255) NOT NULL,
   `description` TEXT,
   `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
   `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  -- bumped by every update, exposed as the ETag of the task
+  `version` INT UNSIGNED NOT NULL DEFAULT 1,
 
   `state` VARCHAR(255) NOT NULL DEFAULT 'todo',
   `priority` VARCHAR(255),
   `due_date` DATETIME,
+  `recurrence` VARCHAR(255),
+  -- hidden from the task list by default since then
+  `archived_at` DATETIME,
+  -- in the trash since then, purged after the retention window
+  `deleted_at` DATETIME,
 
   PRIMARY KEY (`id`),
+  INDEX (`author_id`, `deleted_at`),
+  INDEX (`assignee_id`, `deleted_at`),
+  FULLTEXT (`title`, `description`),
   FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE SET NULL,
+  FOREIGN KEY (`assignee_id`) REFERENCES `users` (`id`) ON DELETE SET NULL,
+  FOREIGN KEY (`parent_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`project_id`) REFERENCES `projects` (`id`) ON DELETE SET NULL,
+  FOREIGN KEY (`workspace_id`) REFERENCES `workspaces` (`id`) ON DELETE CASCADE,
 
   FOREIGN KEY (`state`) REFERENCES `state_mapping` (`state_name`) ON UPDATE CASCADE ON DELETE RESTRICT,
   FOREIGN KEY (`priority`) REFERENCES `priority_mapping` (`priority_name`) ON UPDATE CASCADE ON DELETE RESTRICT
@@ -70,3 +155,149 @@ CREATE TABLE IF NOT EXISTS `todo_taggings` (
   FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
   FOREIGN KEY (`tag_id`) REFERENCES `tags` (`id`) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `todo_dependencies` (
+  `todo_id` VARBINARY(16) NOT NULL,
+  `blocker_id` VARBINARY(16) NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`todo_id`, `blocker_id`),
+  FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`blocker_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `reminders` (
+  `id` VARBINARY(16) NOT NULL,
+  `todo_id` VARBINARY(16) NOT NULL,
+  `author_id` VARBINARY(16) NOT NULL,
+  -- either an absolute time or minutes before the due date of the task
+  `remind_at` DATETIME,
+  `offset_minutes` INT,
+  `channel` VARCHAR(255) NOT NULL,
+  -- webhook URL or email address
+  `target` VARCHAR(255),
+  `fired_at` DATETIME,
+  `attempts` INT NOT NULL DEFAULT 0,
+  `locked_until` DATETIME,
+  `last_error` TEXT,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  INDEX (`fired_at`, `locked_until`),
+  FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `notifications` (
+  `id` VARBINARY(16) NOT NULL,
+  `user_id` VARBINARY(16) NOT NULL,
+  `kind` VARCHAR(255) NOT NULL,
+  `todo_id` VARBINARY(16),
+  `message` TEXT NOT NULL,
+  `read_at` DATETIME,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  INDEX (`user_id`, `read_at`),
+  INDEX (`todo_id`, `kind`),
+  FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `task_comments` (
+  `id` VARBINARY(16) NOT NULL,
+  `todo_id` VARBINARY(16) NOT NULL,
+  `author_id` VARBINARY(16) NOT NULL,
+  `body` TEXT NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  INDEX (`todo_id`, `created_at`),
+  FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `task_events` (
+  `id` VARBINARY(16) NOT NULL,
+  -- kept after the task is deleted, so without foreign key
+  `todo_id` VARBINARY(16) NOT NULL,
+  -- author of the task at the time of the event
+  `author_id` VARBINARY(16),
+  `actor_id` VARBINARY(16),
+  `action` VARCHAR(255) NOT NULL,
+  -- JSON object of `{ "before": ..., "after": ... }` for each changed field
+  `changes` TEXT NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  INDEX (`todo_id`, `created_at`),
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE SET NULL,
+  FOREIGN KEY (`actor_id`) REFERENCES `users` (`id`) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- the author of a task is always its owner
+CREATE TABLE IF NOT EXISTS `task_shares` (
+  `todo_id` VARBINARY(16) NOT NULL,
+  `user_id` VARBINARY(16) NOT NULL,
+  -- `viewer`, `editor` or `owner`
+  `role` VARCHAR(255) NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`todo_id`, `user_id`),
+  INDEX (`user_id`),
+  FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- grants the role on every task of the project
+CREATE TABLE IF NOT EXISTS `project_shares` (
+  `project_id` VARBINARY(16) NOT NULL,
+  `user_id` VARBINARY(16) NOT NULL,
+  -- `viewer`, `editor` or `owner`
+  `role` VARCHAR(255) NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`project_id`, `user_id`),
+  INDEX (`user_id`),
+  FOREIGN KEY (`project_id`) REFERENCES `projects` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `idempotency_keys` (
+  `user_id` VARBINARY(16) NOT NULL,
+  `idempotency_key` VARCHAR(255) NOT NULL,
+  -- JSON of the request, a key cannot be reused for another request
+  `request` TEXT NOT NULL,
+  -- the response replayed for the key, unset until the request is done
+  `status` SMALLINT UNSIGNED,
+  `response` TEXT,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`user_id`, `idempotency_key`),
+  INDEX (`created_at`),
+  FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS `task_views` (
+  `id` VARBINARY(16) NOT NULL,
+  `author_id` VARBINARY(16) NOT NULL,
+  `name` VARCHAR(255) NOT NULL,
+  `query` TEXT NOT NULL,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updated_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  UNIQUE (`author_id`, `name`),
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- read-only access to a task or a view for anyone holding the token, without an account
+CREATE TABLE IF NOT EXISTS `share_links` (
+  `token` VARCHAR(64) NOT NULL,
+  `author_id` VARBINARY(16) NOT NULL,
+  -- exactly one of `todo_id` and `view_id` is set
+  `todo_id` VARBINARY(16),
+  `view_id` VARBINARY(16),
+  -- never expires when unset
+  `expires_at` DATETIME,
+  `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`token`),
+  INDEX (`author_id`),
+  INDEX (`expires_at`),
+  FOREIGN KEY (`author_id`) REFERENCES `users` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`todo_id`) REFERENCES `todos` (`id`) ON DELETE CASCADE,
+  FOREIGN KEY (`view_id`) REFERENCES `task_views` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
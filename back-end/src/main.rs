@@ -1,8 +1,9 @@
 mod model;
 mod router;
+mod scheduler;
 mod utils;
 
-use std::env;
+use std::{env, time::Duration};
 
 use actix_cors::Cors;
 use actix_session::{storage::CookieSessionStore, Session, SessionMiddleware};
@@ -10,7 +11,11 @@ use actix_web::{cookie::Key, get, http, web::Data, App, HttpResponse, HttpServer
 use dotenv::dotenv;
 use sqlx::mysql::MySqlPoolOptions;
 
-use crate::router::{account::account_router, task::tasks_router};
+use crate::router::{
+    account::account_router, notification::notifications_router, project::projects_router,
+    share_link::share_links_router, tag::tags_router, task::tasks_router,
+    workspace::workspaces_router,
+};
 
 #[get("")]
 async fn hello_world(session: Session) -> impl Responder {
@@ -50,6 +55,37 @@ async fn main() -> std::io::Result<()> {
     let tables = sqlx::query(query).fetch_all(&pool).await.unwrap();
     println!("Tables: {:?}", tables);
 
+    let mut scheduler = scheduler::Scheduler::new(pool.clone(), Duration::from_secs(30))
+        .with_delivery(scheduler::InAppDelivery::new(pool.clone()))
+        .with_delivery(scheduler::WebhookDelivery::new(Duration::from_secs(10)));
+    if let Ok(smtp_host) = env::var("SMTP_HOST") {
+        let smtp_port = env::var("SMTP_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(587);
+        let smtp_credentials = env::var("SMTP_USERNAME")
+            .ok()
+            .zip(env::var("SMTP_PASSWORD").ok());
+        let smtp_from = env::var("SMTP_FROM").unwrap();
+        scheduler = scheduler.with_delivery(
+            scheduler::EmailDelivery::new(
+                &smtp_host,
+                smtp_port,
+                smtp_credentials,
+                &smtp_from,
+                Duration::from_secs(30),
+            )
+            .unwrap(),
+        );
+    }
+    if let Some(days) = env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|d| d.parse::<u64>().ok())
+    {
+        scheduler = scheduler.with_trash_retention(Duration::from_secs(days * 24 * 60 * 60));
+    }
+    scheduler.spawn();
+
     HttpServer::new(move || {
         let cors = use_cors_middleware();
 
@@ -64,7 +100,12 @@ async fn main() -> std::io::Result<()> {
             .app_data(Data::new(pool.clone()))
             .service(hello_world)
             .service(tasks_router())
+            .service(tags_router())
+            .service(projects_router())
             .service(account_router())
+            .service(notifications_router())
+            .service(workspaces_router())
+            .service(share_links_router())
     })
     .bind(("0.0.0.0", 8080))?
     .run()
@@ -80,6 +121,12 @@ fn use_cors_middleware() -> Cors {
             http::header::ACCEPT,
             http::header::CONTENT_TYPE,
             http::header::SET_COOKIE,
+            http::header::IF_MATCH,
+            http::header::HeaderName::from_static("idempotency-key"),
+        ])
+        .expose_headers(vec![
+            http::header::ETAG,
+            http::header::HeaderName::from_static("idempotent-replayed"),
         ])
         .supports_credentials()
         .max_age(3600)
@@ -0,0 +1,122 @@
+use sqlx::{Acquire, MySql, Row};
+
+use super::types::VecWithTotal;
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// oldest first
+pub async fn get_comments_of_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    limit: usize,
+    offset: usize,
+) -> anyhow::Result<VecWithTotal<types::TaskComment>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `task_comments` WHERE `todo_id` = ?
+            ORDER BY `created_at` ASC, `id` ASC
+            LIMIT ? OFFSET ?;"#;
+
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let items = sqlx::query_as::<_, types::TaskComment>(query)
+        .bind(bin_task_id.as_slice())
+        .bind(limit as u64)
+        .bind(offset as u64)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let query = "SELECT COUNT(*) FROM `task_comments` WHERE `todo_id` = ?;";
+    let total = sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(VecWithTotal {
+        total: Some(total as usize),
+        items,
+        tag_counts: None,
+        next_cursor: None,
+        prev_cursor: None,
+    })
+}
+
+pub async fn get_comment(
+    conn: impl Acquire<'_, Database = MySql>,
+    comment_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::TaskComment>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `task_comments` WHERE `id` = ?;";
+
+    let bin_comment_id = ulid_to_binary(comment_id);
+
+    let row = sqlx::query_as::<_, types::TaskComment>(query)
+        .bind(bin_comment_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn insert_comment(
+    conn: impl Acquire<'_, Database = MySql>,
+    comment: types::TaskCommentReq,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `task_comments` (`id`, `todo_id`, `author_id`, `body`)
+            VALUES (?, ?, ?, ?);"#;
+
+    sqlx::query(query)
+        .bind(comment.id)
+        .bind(comment.todo_id)
+        .bind(comment.author_id)
+        .bind(comment.body)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_comment(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    body: &str,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "UPDATE `task_comments` SET `body` = ? WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(body)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_comment(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `task_comments` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
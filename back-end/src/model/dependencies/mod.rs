@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use sqlx::{Acquire, FromRow, MySql, Row};
+
+use crate::utils::{binary_to_ulid, ulid_to_binary};
+
+use super::types;
+
+/// whether `task_id` is already one of the blockers of `blocker_id`, directly or transitively,
+/// so that blocking `task_id` by `blocker_id` would make a cycle
+pub async fn is_blocked_transitively(
+    conn: impl Acquire<'_, Database = MySql>,
+    blocker_id: ulid::Ulid,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<bool> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        WITH RECURSIVE `blockers` AS (
+            SELECT `blocker_id` AS `id` FROM `todo_dependencies` WHERE `todo_id` = ?
+            UNION
+            SELECT `todo_dependencies`.`blocker_id` FROM `todo_dependencies`
+                INNER JOIN `blockers` ON `todo_dependencies`.`todo_id` = `blockers`.`id`
+        )
+        SELECT COUNT(*) FROM `blockers` WHERE `id` = ?;"#;
+
+    let bin_blocker_id = ulid_to_binary(blocker_id);
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let count = sqlx::query(query)
+        .bind(bin_blocker_id.as_slice())
+        .bind(bin_task_id.as_slice())
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(count > 0)
+}
+
+pub async fn add_blocker(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    blocker_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "INSERT IGNORE INTO `todo_dependencies` (`todo_id`, `blocker_id`) VALUES (?, ?);";
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_blocker_id = ulid_to_binary(blocker_id);
+
+    sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_blocker_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn remove_blocker(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    blocker_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `todo_dependencies` WHERE `todo_id` = ? AND `blocker_id` = ?;";
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_blocker_id = ulid_to_binary(blocker_id);
+
+    sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_blocker_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct UnfinishedBlocker {
+    #[sqlx(flatten)]
+    linked: types::LinkedTask,
+    is_visible: i64,
+}
+
+/// `(visible, hidden)`: direct blockers of the task which are not done yet and the user can
+/// see, and how many others there are
+pub async fn get_unfinished_blockers(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<(Vec<types::LinkedTask>, usize)> {
+    let mut conn = conn.acquire().await?;
+
+    let query = format!(
+        r#"
+        SELECT `todos`.`id`, `todos`.`title`, `todos`.`state`, CAST({} AS SIGNED) AS `is_visible`
+            FROM `todo_dependencies`
+            INNER JOIN `todos` ON `todos`.`id` = `todo_dependencies`.`blocker_id`
+            WHERE `todo_dependencies`.`todo_id` = ? AND `todos`.`state` <> 'done'
+            AND `todos`.`deleted_at` IS NULL
+            ORDER BY `todos`.`created_at` ASC;"#,
+        super::shares::VISIBLE_TASK_QUERY
+    );
+
+    let bin_user_id = ulid_to_binary(user_id);
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let mut building_query = sqlx::query_as::<_, UnfinishedBlocker>(query.as_str());
+    for _ in 0..super::shares::VISIBLE_TASK_BINDS {
+        building_query = building_query.bind(bin_user_id.as_slice());
+    }
+    let rows = building_query
+        .bind(bin_task_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let (visible, hidden): (Vec<_>, Vec<_>) = rows.into_iter().partition(|row| row.is_visible != 0);
+
+    Ok((
+        visible.into_iter().map(|row| row.linked).collect(),
+        hidden.len(),
+    ))
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct Dependency {
+    task_id: Vec<u8>,
+    #[sqlx(flatten)]
+    linked: types::LinkedTask,
+}
+
+/// `(blockers, dependents)` of each task, leaving out the linked tasks the user cannot see
+pub async fn get_dependencies_of_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    task_ids: &[ulid::Ulid],
+) -> anyhow::Result<(
+    HashMap<ulid::Ulid, Vec<types::LinkedTask>>,
+    HashMap<ulid::Ulid, Vec<types::LinkedTask>>,
+)> {
+    let mut conn = conn.acquire().await?;
+
+    if task_ids.is_empty() {
+        return Ok((HashMap::new(), HashMap::new()));
+    }
+
+    let placeholders = vec!["?"; task_ids.len()].join(", ");
+    // blockers are joined on `blocker_id` and keyed by `todo_id`, dependents the other way round
+    let query = |task_column: &str, linked_column: &str| {
+        format!(
+            r#"
+            SELECT
+                `todo_dependencies`.`{task_column}` AS `task_id`,
+                `todos`.`id`, `todos`.`title`, `todos`.`state`
+                FROM `todo_dependencies`
+                INNER JOIN `todos` ON `todos`.`id` = `todo_dependencies`.`{linked_column}`
+                WHERE `todo_dependencies`.`{task_column}` IN ({placeholders})
+                AND `todos`.`deleted_at` IS NULL AND {visible}
+                ORDER BY `todos`.`created_at` ASC;"#,
+            visible = super::shares::VISIBLE_TASK_QUERY
+        )
+    };
+
+    let bin_user_id = ulid_to_binary(user_id);
+    let bin_task_ids = task_ids
+        .iter()
+        .map(|id| ulid_to_binary(*id))
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+    for query in [
+        query("todo_id", "blocker_id"),
+        query("blocker_id", "todo_id"),
+    ] {
+        let mut building_query = sqlx::query_as::<_, Dependency>(query.as_str());
+        for bin_task_id in bin_task_ids.iter() {
+            building_query = building_query.bind(bin_task_id.as_slice());
+        }
+        for _ in 0..super::shares::VISIBLE_TASK_BINDS {
+            building_query = building_query.bind(bin_user_id.as_slice());
+        }
+
+        let rows = building_query.fetch_all(&mut *conn).await?;
+
+        let mut linked: HashMap<ulid::Ulid, Vec<types::LinkedTask>> = HashMap::new();
+        for row in rows {
+            let task_id = binary_to_ulid(row.task_id.as_slice())?;
+            linked.entry(task_id).or_default().push(row.linked);
+        }
+        results.push(linked);
+    }
+
+    let dependents = results.pop().unwrap_or_default();
+    let blockers = results.pop().unwrap_or_default();
+
+    Ok((blockers, dependents))
+}
@@ -0,0 +1,251 @@
+use serde_json::{json, Map, Value};
+use sqlx::{Acquire, MySql};
+
+use super::{tasks::UpdateTask, Update};
+use crate::utils::{binary_to_ulid, ulid_to_binary};
+
+use super::types;
+
+/// values of the fields of `UpdateTask`, in its order
+fn field_values(
+    title: &str,
+    description: &str,
+    state: types::TaskState,
+    priority: Option<types::TaskPriority>,
+    due_date: Option<chrono::NaiveDateTime>,
+    recurrence: Option<&types::Recurrence>,
+    assignee_id: Option<&[u8]>,
+) -> [(&'static str, Value); 7] {
+    [
+        ("title", json!(title)),
+        ("description", json!(description)),
+        ("state", json!(state.to_string())),
+        ("priority", json!(priority.map(|p| p.to_string()))),
+        (
+            "due_date",
+            json!(due_date.map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())),
+        ),
+        ("recurrence", json!(recurrence.map(|r| r.to_string()))),
+        ("assignee_id", assignee_value(assignee_id)),
+    ]
+}
+
+/// the assignee as a ULID string, like in the API
+fn assignee_value(assignee_id: Option<&[u8]>) -> Value {
+    json!(assignee_id
+        .and_then(|id| binary_to_ulid(id).ok())
+        .map(|id| id.to_string()))
+}
+
+fn task_values(task: &types::Todo) -> [(&'static str, Value); 7] {
+    field_values(
+        &task.title,
+        &task.description,
+        task.state,
+        task.priority,
+        task.due_date,
+        task.recurrence.as_ref(),
+        task.assignee_id.as_deref(),
+    )
+}
+
+/// values of the fields set by the update, `None` for the others
+fn update_values(update: &UpdateTask) -> [(&'static str, Option<Value>); 7] {
+    fn value<T>(update: &Update<T>, f: impl FnOnce(&T) -> Value) -> Option<Value> {
+        match update {
+            Update::Set(t) => Some(f(t)),
+            Update::Nop => None,
+        }
+    }
+
+    [
+        ("title", value(&update.title, |t| json!(t))),
+        ("description", value(&update.description, |d| json!(d))),
+        ("state", value(&update.state, |s| json!(s.to_string()))),
+        (
+            "priority",
+            value(&update.priority, |p| json!(p.map(|p| p.to_string()))),
+        ),
+        (
+            "due_date",
+            value(&update.due_date, |d| {
+                json!(d.map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()))
+            }),
+        ),
+        (
+            "recurrence",
+            value(&update.recurrence, |r| {
+                json!(r.as_ref().map(|r| r.to_string()))
+            }),
+        ),
+        (
+            "assignee_id",
+            value(&update.assignee_id, |a| assignee_value(a.as_deref())),
+        ),
+    ]
+}
+
+async fn insert_event(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: &[u8],
+    author_id: Option<&[u8]>,
+    actor_id: ulid::Ulid,
+    action: types::TaskEventAction,
+    changes: Map<String, Value>,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `task_events` (`id`, `todo_id`, `author_id`, `actor_id`, `action`, `changes`)
+            VALUES (?, ?, ?, ?, ?, ?);"#;
+
+    let bin_event_id = ulid_to_binary(ulid::Ulid::new());
+    let bin_actor_id = ulid_to_binary(actor_id);
+
+    sqlx::query(query)
+        .bind(bin_event_id.as_slice())
+        .bind(task_id)
+        .bind(author_id)
+        .bind(bin_actor_id.as_slice())
+        .bind(action)
+        .bind(Value::Object(changes).to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// records the creation of the task by `actor_id`, with all of its fields as `after`
+pub async fn record_creation(
+    conn: impl Acquire<'_, Database = MySql>,
+    actor_id: ulid::Ulid,
+    task: &types::TodoReq,
+) -> anyhow::Result<()> {
+    let changes = field_values(
+        &task.title,
+        &task.description,
+        task.state,
+        task.priority,
+        task.due_date,
+        task.recurrence.as_ref(),
+        task.assignee_id.as_deref(),
+    )
+    .into_iter()
+    .map(|(field, after)| (field.to_string(), json!({ "before": null, "after": after })))
+    .collect();
+
+    insert_event(
+        conn,
+        &task.id,
+        task.author_id.as_deref(),
+        actor_id,
+        types::TaskEventAction::Create,
+        changes,
+    )
+    .await
+}
+
+/// records the fields that `update` changes in `before`, the row locked before updating it
+///
+/// nothing is recorded when no field changes
+pub async fn record_update(
+    conn: impl Acquire<'_, Database = MySql>,
+    actor_id: ulid::Ulid,
+    before: &types::Todo,
+    update: &UpdateTask,
+) -> anyhow::Result<()> {
+    let changes = task_values(before)
+        .into_iter()
+        .zip(update_values(update))
+        .filter_map(|((field, before), (_, after))| {
+            after.filter(|after| after != &before).map(|after| {
+                (
+                    field.to_string(),
+                    json!({ "before": before, "after": after }),
+                )
+            })
+        })
+        .collect::<Map<_, _>>();
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    insert_event(
+        conn,
+        &before.id,
+        before.author_id.as_deref(),
+        actor_id,
+        types::TaskEventAction::Update,
+        changes,
+    )
+    .await
+}
+
+/// records the deletion of the task by `actor_id`, with all of its fields as `before`
+pub async fn record_deletion(
+    conn: impl Acquire<'_, Database = MySql>,
+    actor_id: ulid::Ulid,
+    before: &types::Todo,
+) -> anyhow::Result<()> {
+    let changes = task_values(before)
+        .into_iter()
+        .map(|(field, before)| {
+            (
+                field.to_string(),
+                json!({ "before": before, "after": null }),
+            )
+        })
+        .collect();
+
+    insert_event(
+        conn,
+        &before.id,
+        before.author_id.as_deref(),
+        actor_id,
+        types::TaskEventAction::Delete,
+        changes,
+    )
+    .await
+}
+
+/// records the restoration of the task from the trash, with all of its fields as `after`
+pub async fn record_restoration(
+    conn: impl Acquire<'_, Database = MySql>,
+    actor_id: ulid::Ulid,
+    after: &types::Todo,
+) -> anyhow::Result<()> {
+    let changes = task_values(after)
+        .into_iter()
+        .map(|(field, after)| (field.to_string(), json!({ "before": null, "after": after })))
+        .collect();
+
+    insert_event(
+        conn,
+        &after.id,
+        after.author_id.as_deref(),
+        actor_id,
+        types::TaskEventAction::Restore,
+        changes,
+    )
+    .await
+}
+
+/// oldest first, including the events of the task after it was deleted
+pub async fn get_task_events(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::TaskEvent>> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "SELECT * FROM `task_events` WHERE `todo_id` = ? ORDER BY `created_at` ASC, `id` ASC;";
+
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let rows = sqlx::query_as::<_, types::TaskEvent>(query)
+        .bind(bin_task_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
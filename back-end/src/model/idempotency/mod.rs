@@ -0,0 +1,104 @@
+use sqlx::{Acquire, MySql};
+
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// how long a key is remembered, a retry after that is handled as a new request
+pub const RETENTION_SECS: i64 = 24 * 60 * 60;
+
+/// claims the key for `request`, returning `None` when it was not used yet
+///
+/// the key stays locked until the transaction ends, so that a concurrent retry waits for the
+/// response of the first request instead of running it again
+pub async fn claim_idempotency_key(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    key: &str,
+    request: &str,
+) -> anyhow::Result<Option<types::IdempotencyKey>> {
+    let mut conn = conn.acquire().await?;
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let query = r#"
+        DELETE FROM `idempotency_keys`
+            WHERE `user_id` = ? AND `idempotency_key` = ?
+            AND `created_at` < NOW() - INTERVAL ? SECOND;"#;
+
+    sqlx::query(query)
+        .bind(bin_user_id.as_slice())
+        .bind(key)
+        .bind(RETENTION_SECS)
+        .execute(&mut *conn)
+        .await?;
+
+    let query = r#"
+        INSERT IGNORE INTO `idempotency_keys` (`user_id`, `idempotency_key`, `request`)
+            VALUES (?, ?, ?);"#;
+
+    let result = sqlx::query(query)
+        .bind(bin_user_id.as_slice())
+        .bind(key)
+        .bind(request)
+        .execute(&mut *conn)
+        .await?;
+    if result.rows_affected() > 0 {
+        return Ok(None);
+    }
+
+    let query = r#"
+        SELECT `request`, `status`, `response` FROM `idempotency_keys`
+            WHERE `user_id` = ? AND `idempotency_key` = ? FOR UPDATE;"#;
+
+    let row = sqlx::query_as::<_, types::IdempotencyKey>(query)
+        .bind(bin_user_id.as_slice())
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+/// stores the response to replay for the key claimed by `claim_idempotency_key`
+pub async fn save_idempotent_response(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    key: &str,
+    status: u16,
+    response: &str,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        UPDATE `idempotency_keys` SET `status` = ?, `response` = ?
+            WHERE `user_id` = ? AND `idempotency_key` = ?;"#;
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(status)
+        .bind(response)
+        .bind(bin_user_id.as_slice())
+        .bind(key)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// forgets the keys older than `RETENTION_SECS`, returning how many were forgotten
+pub async fn purge_expired_idempotency_keys(
+    conn: impl Acquire<'_, Database = MySql>,
+) -> anyhow::Result<u64> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `idempotency_keys` WHERE `created_at` < NOW() - INTERVAL ? SECOND;";
+
+    let result = sqlx::query(query)
+        .bind(RETENTION_SECS)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(result.rows_affected())
+}
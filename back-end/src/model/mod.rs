@@ -1,12 +1,25 @@
 use sqlx::mysql::MySqlArguments;
 
+pub mod comments;
+pub mod dependencies;
+pub mod history;
+pub mod idempotency;
+pub mod notifications;
+pub mod projects;
+pub mod reminders;
+pub mod share_links;
+pub mod shares;
+pub mod tags;
 pub mod tasks;
 pub mod types;
 pub mod users;
+pub mod views;
+pub mod workspaces;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum Update<T> {
     Set(T),
+    #[default]
     Nop,
 }
 impl<T> Update<T> {
@@ -71,11 +84,6 @@ where
         }
     }
 }
-impl<T> Default for Update<T> {
-    fn default() -> Self {
-        Self::Nop
-    }
-}
 impl<'de, T> serde::Deserialize<'de> for Update<T>
 where
     T: serde::Deserialize<'de>,
@@ -88,3 +96,11 @@ where
         Ok(Self::Set(t))
     }
 }
+
+/// whether the query failed on a unique key, such as an id inserted twice at the same time
+pub fn is_duplicate_key(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .and_then(|e| e.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>())
+        .is_some_and(|e| e.number() == 1062)
+}
@@ -0,0 +1,187 @@
+use sqlx::{Acquire, MySql, Row};
+
+use super::types::VecWithTotal;
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// adds a notification to the inbox of the user
+///
+/// this is the only way notifications are created, whatever the event behind them
+pub async fn enqueue_notification(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    kind: types::NotificationKind,
+    task_id: Option<ulid::Ulid>,
+    message: &str,
+) -> anyhow::Result<ulid::Ulid> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `notifications` (`id`, `user_id`, `kind`, `todo_id`, `message`)
+            VALUES (?, ?, ?, ?, ?);"#;
+
+    let notification_id = ulid::Ulid::new();
+    let bin_notification_id = ulid_to_binary(notification_id);
+    let bin_user_id = ulid_to_binary(user_id);
+    let bin_task_id = task_id.map(|id| ulid_to_binary(id).to_vec());
+
+    sqlx::query(query)
+        .bind(bin_notification_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .bind(kind)
+        .bind(bin_task_id)
+        .bind(message)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(notification_id)
+}
+
+/// newest first
+pub async fn get_notifications(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    unread_only: bool,
+    limit: usize,
+    offset: usize,
+) -> anyhow::Result<VecWithTotal<types::Notification>> {
+    let mut conn = conn.acquire().await?;
+
+    let condition = if unread_only {
+        "`user_id` = ? AND `read_at` IS NULL"
+    } else {
+        "`user_id` = ?"
+    };
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let query = format!(
+        "SELECT * FROM `notifications` WHERE {} ORDER BY `id` DESC LIMIT ? OFFSET ?;",
+        condition
+    );
+    let items = sqlx::query_as::<_, types::Notification>(query.as_str())
+        .bind(bin_user_id.as_slice())
+        .bind(limit as u64)
+        .bind(offset as u64)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let query = format!("SELECT COUNT(*) FROM `notifications` WHERE {};", condition);
+    let total = sqlx::query(query.as_str())
+        .bind(bin_user_id.as_slice())
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(VecWithTotal {
+        total: Some(total as usize),
+        items,
+        tag_counts: None,
+        next_cursor: None,
+        prev_cursor: None,
+    })
+}
+
+pub async fn get_notification(
+    conn: impl Acquire<'_, Database = MySql>,
+    notification_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Notification>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `notifications` WHERE `id` = ?;";
+
+    let bin_notification_id = ulid_to_binary(notification_id);
+
+    let row = sqlx::query_as::<_, types::Notification>(query)
+        .bind(bin_notification_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn mark_notification_read(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "UPDATE `notifications` SET `read_at` = NOW() WHERE `id` = ? AND `read_at` IS NULL;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn mark_all_notifications_read(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "UPDATE `notifications` SET `read_at` = NOW() WHERE `user_id` = ? AND `read_at` IS NULL;";
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_user_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_notification(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `notifications` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// overdue tasks whose author has not been notified since the task was last due
+pub async fn get_unnotified_overdue_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    limit: usize,
+) -> anyhow::Result<Vec<types::Todo>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `todos`
+            WHERE `todos`.`due_date` < NOW() AND `todos`.`state` <> 'done'
+            AND `todos`.`author_id` IS NOT NULL AND `todos`.`deleted_at` IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM `notifications`
+                    WHERE `notifications`.`todo_id` = `todos`.`id`
+                    AND `notifications`.`kind` = ?
+                    AND `notifications`.`created_at` >= `todos`.`due_date`
+            )
+            ORDER BY `todos`.`due_date` ASC
+            LIMIT ?;"#;
+
+    let rows = sqlx::query_as::<_, types::Todo>(query)
+        .bind(types::NotificationKind::Overdue)
+        .bind(limit as u64)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
@@ -0,0 +1,238 @@
+use sqlx::{mysql::MySqlArguments, Acquire, MySql, Row};
+
+use super::Update;
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// projects of the user along with the ones shared with them, sorted by name, archived
+/// projects only with `include_archived`
+pub async fn get_projects(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    include_archived: bool,
+) -> anyhow::Result<Vec<types::Project>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = format!(
+        "SELECT * FROM `projects` WHERE {}{} ORDER BY `name` ASC;",
+        super::shares::VISIBLE_PROJECT_QUERY,
+        if include_archived {
+            ""
+        } else {
+            " AND `archived_at` IS NULL"
+        }
+    );
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let rows = sqlx::query_as::<_, types::Project>(query.as_str())
+        .bind(bin_user_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    project_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Project>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `projects` WHERE `id` = ?;";
+
+    let bin_project_id = ulid_to_binary(project_id);
+
+    let row = sqlx::query_as::<_, types::Project>(query)
+        .bind(bin_project_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn get_project_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    project_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Project>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `projects` WHERE `id` = ? FOR UPDATE;";
+
+    let bin_project_id = ulid_to_binary(project_id);
+
+    let row = sqlx::query_as::<_, types::Project>(query)
+        .bind(bin_project_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn is_project_name_exists(
+    conn: impl Acquire<'_, Database = MySql>,
+    author_id: ulid::Ulid,
+    name: &str,
+) -> anyhow::Result<bool> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT COUNT(*) FROM `projects` WHERE `author_id` = ? AND `name` = ?;";
+
+    let bin_author_id = ulid_to_binary(author_id);
+
+    let count = sqlx::query(query)
+        .bind(bin_author_id.as_slice())
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(count > 0)
+}
+
+pub async fn insert_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    project: types::ProjectReq,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `projects` (`id`, `author_id`, `name`, `description`, `color`, `icon`)
+            VALUES (?, ?, ?, ?, ?, ?);"#;
+
+    sqlx::query(query)
+        .bind(project.id)
+        .bind(project.author_id)
+        .bind(project.name)
+        .bind(project.description)
+        .bind(project.color)
+        .bind(project.icon)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateProject {
+    pub name: Update<String>,
+    pub description: Update<String>,
+    pub color: Update<Option<String>>,
+    pub icon: Update<Option<String>>,
+}
+impl UpdateProject {
+    fn to_prepared_query(&self) -> String {
+        let mut query = Vec::new();
+
+        if let Some(q) = self.name.to_prepared_query("name") {
+            query.push(q);
+        }
+        if let Some(q) = self.description.to_prepared_query("description") {
+            query.push(q);
+        }
+        if let Some(q) = self.color.to_prepared_query("color") {
+            query.push(q);
+        }
+        if let Some(q) = self.icon.to_prepared_query("icon") {
+            query.push(q);
+        }
+
+        query.join(", ")
+    }
+
+    pub fn bind_query<'a>(
+        &'a self,
+        query: sqlx::query::Query<'a, sqlx::MySql, MySqlArguments>,
+    ) -> sqlx::query::Query<'a, sqlx::MySql, MySqlArguments> {
+        let mut query = self.name.bind_query(query);
+        query = self.description.bind_query(query);
+        query = self.color.bind_query(query);
+        query = self.icon.bind_query(query);
+
+        query
+    }
+
+    pub fn is_nop(&self) -> bool {
+        self.name.is_nop() && self.description.is_nop() && self.color.is_nop() && self.icon.is_nop()
+    }
+}
+
+pub async fn update_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    update: UpdateProject,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    if update.is_nop() {
+        return Ok(());
+    }
+
+    let query = format!(
+        "UPDATE `projects` SET {} WHERE `id` = ?;",
+        update.to_prepared_query()
+    );
+
+    let bin_id = ulid_to_binary(id);
+
+    let building_query = update
+        .bind_query(sqlx::query(query.as_str()))
+        .bind(bin_id.as_slice());
+
+    building_query.execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// archives the project, or takes it out of the archive with `false`
+pub async fn set_archived(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    archived: bool,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = if archived {
+        "UPDATE `projects` SET `archived_at` = NOW() WHERE `id` = ? AND `archived_at` IS NULL;"
+    } else {
+        "UPDATE `projects` SET `archived_at` = NULL WHERE `id` = ?;"
+    };
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// the tasks of the project go back to the inbox
+pub async fn delete_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let bin_id = ulid_to_binary(id);
+
+    let query =
+        "UPDATE `todos` SET `project_id` = NULL, `version` = `version` + 1 WHERE `project_id` = ?;";
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    let query = "DELETE FROM `projects` WHERE `id` = ?;";
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use sqlx::{Acquire, MySql};
+
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// reminders the user set on the task, those of the others with access to it stay private
+pub async fn get_reminders_of_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    author_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Reminder>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `reminders`
+            WHERE `todo_id` = ? AND `author_id` = ?
+            ORDER BY `created_at` ASC;"#;
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_author_id = ulid_to_binary(author_id);
+
+    let rows = sqlx::query_as::<_, types::Reminder>(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_author_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_reminder(
+    conn: impl Acquire<'_, Database = MySql>,
+    reminder_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Reminder>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `reminders` WHERE `id` = ?;";
+
+    let bin_reminder_id = ulid_to_binary(reminder_id);
+
+    let row = sqlx::query_as::<_, types::Reminder>(query)
+        .bind(bin_reminder_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn insert_reminder(
+    conn: impl Acquire<'_, Database = MySql>,
+    reminder: types::ReminderReq,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `reminders`
+            (`id`, `todo_id`, `author_id`, `remind_at`, `offset_minutes`, `channel`, `target`)
+            VALUES (?, ?, ?, ?, ?, ?, ?);"#;
+
+    sqlx::query(query)
+        .bind(reminder.id)
+        .bind(reminder.todo_id)
+        .bind(reminder.author_id)
+        .bind(reminder.remind_at)
+        .bind(reminder.offset_minutes)
+        .bind(reminder.channel)
+        .bind(reminder.target)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_reminder(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `reminders` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// locks up to `limit` reminders that are due and not delivered yet for `lease_secs` seconds,
+/// counting a delivery attempt for each
+///
+/// a reminder whose delivery did not finish, e.g. because the server stopped, is claimed again
+/// once its lease runs out
+pub async fn claim_due_reminders(
+    conn: impl Acquire<'_, Database = MySql>,
+    limit: usize,
+    max_attempts: i32,
+    lease_secs: i64,
+) -> anyhow::Result<Vec<types::DueReminder>> {
+    let mut conn = conn.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    let query = r#"
+        SELECT `reminders`.*, `todos`.`title`, `todos`.`due_date` FROM `reminders`
+            INNER JOIN `todos` ON `todos`.`id` = `reminders`.`todo_id`
+            WHERE `reminders`.`fired_at` IS NULL
+            AND `todos`.`deleted_at` IS NULL
+            AND `reminders`.`attempts` < ?
+            AND (`reminders`.`locked_until` IS NULL OR `reminders`.`locked_until` <= NOW())
+            AND COALESCE(
+                `reminders`.`remind_at`,
+                `todos`.`due_date` - INTERVAL `reminders`.`offset_minutes` MINUTE
+            ) <= NOW()
+            ORDER BY `reminders`.`id` ASC
+            LIMIT ?
+            FOR UPDATE SKIP LOCKED;"#;
+
+    let rows = sqlx::query_as::<_, types::DueReminder>(query)
+        .bind(max_attempts)
+        .bind(limit as u64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    if !rows.is_empty() {
+        let query = format!(
+            r#"
+            UPDATE `reminders`
+                SET `locked_until` = NOW() + INTERVAL ? SECOND, `attempts` = `attempts` + 1
+                WHERE `id` IN ({});"#,
+            vec!["?"; rows.len()].join(", ")
+        );
+
+        let mut building_query = sqlx::query(query.as_str()).bind(lease_secs);
+        for row in rows.iter() {
+            building_query = building_query.bind(row.reminder.id.as_slice());
+        }
+        building_query.execute(&mut *tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(rows)
+}
+
+pub async fn mark_reminder_fired(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: &[u8],
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        UPDATE `reminders`
+            SET `fired_at` = NOW(), `locked_until` = NULL, `last_error` = NULL
+            WHERE `id` = ?;"#;
+
+    sqlx::query(query).bind(id).execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// records the failure and keeps the reminder locked for `retry_after_secs` seconds
+pub async fn mark_reminder_failed(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: &[u8],
+    error: &str,
+    retry_after_secs: i64,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        UPDATE `reminders`
+            SET `locked_until` = NOW() + INTERVAL ? SECOND, `last_error` = ?
+            WHERE `id` = ?;"#;
+
+    sqlx::query(query)
+        .bind(retry_after_secs)
+        .bind(error)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use sqlx::{Acquire, MySql};
+
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// links of the author that did not expire, newest first
+pub async fn get_share_links(
+    conn: impl Acquire<'_, Database = MySql>,
+    author_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::ShareLink>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `share_links`
+            WHERE `author_id` = ? AND (`expires_at` IS NULL OR `expires_at` > NOW())
+            ORDER BY `created_at` DESC;"#;
+
+    let bin_author_id = ulid_to_binary(author_id);
+
+    let rows = sqlx::query_as::<_, types::ShareLink>(query)
+        .bind(bin_author_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+/// the link behind the token, `None` once expired
+pub async fn get_share_link(
+    conn: impl Acquire<'_, Database = MySql>,
+    token: &str,
+) -> anyhow::Result<Option<types::ShareLink>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `share_links`
+            WHERE `token` = ? AND (`expires_at` IS NULL OR `expires_at` > NOW());"#;
+
+    let row = sqlx::query_as::<_, types::ShareLink>(query)
+        .bind(token)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn get_share_link_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    token: &str,
+) -> anyhow::Result<Option<types::ShareLink>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `share_links`
+            WHERE `token` = ? AND (`expires_at` IS NULL OR `expires_at` > NOW())
+            FOR UPDATE;"#;
+
+    let row = sqlx::query_as::<_, types::ShareLink>(query)
+        .bind(token)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn insert_share_link(
+    conn: impl Acquire<'_, Database = MySql>,
+    link: types::ShareLinkReq,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `share_links` (`token`, `author_id`, `todo_id`, `view_id`, `expires_at`)
+            VALUES (?, ?, ?, ?, ?);"#;
+
+    sqlx::query(query)
+        .bind(link.token)
+        .bind(link.author_id)
+        .bind(link.todo_id)
+        .bind(link.view_id)
+        .bind(link.expires_at)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_share_link(
+    conn: impl Acquire<'_, Database = MySql>,
+    token: &str,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `share_links` WHERE `token` = ?;";
+
+    sqlx::query(query).bind(token).execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// forgets the expired links, returning how many were forgotten
+pub async fn purge_expired_share_links(
+    conn: impl Acquire<'_, Database = MySql>,
+) -> anyhow::Result<u64> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `share_links` WHERE `expires_at` <= NOW();";
+
+    let result = sqlx::query(query).execute(&mut *conn).await?;
+
+    Ok(result.rows_affected())
+}
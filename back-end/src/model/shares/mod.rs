@@ -0,0 +1,388 @@
+use sqlx::{Acquire, MySql, Row};
+
+use crate::utils::{binary_to_ulid, ulid_to_binary};
+
+use super::types;
+
+/// condition on `todos` matching the tasks of the user, the tasks in their projects and the
+/// ones shared with them, directly, through their project or through their workspace, binding
+/// the user id `VISIBLE_TASK_BINDS` times
+pub const VISIBLE_TASK_QUERY: &str = r#"(`todos`.`author_id` = ?
+    OR EXISTS (
+        SELECT 1 FROM `projects`
+            WHERE `projects`.`id` = `todos`.`project_id` AND `projects`.`author_id` = ?)
+    OR EXISTS (
+        SELECT 1 FROM `task_shares`
+            WHERE `task_shares`.`todo_id` = `todos`.`id` AND `task_shares`.`user_id` = ?)
+    OR EXISTS (
+        SELECT 1 FROM `project_shares`
+            WHERE `project_shares`.`project_id` = `todos`.`project_id`
+            AND `project_shares`.`user_id` = ?)
+    OR EXISTS (
+        SELECT 1 FROM `workspace_members`
+            WHERE `workspace_members`.`workspace_id` = `todos`.`workspace_id`
+            AND `workspace_members`.`user_id` = ?))"#;
+pub const VISIBLE_TASK_BINDS: usize = 5;
+
+/// condition on `todos` matching the tasks the user owns, as their author, as the author of
+/// their project or as an owner by share or by workspace role, binding the user id
+/// `OWNED_TASK_BINDS` times
+pub const OWNED_TASK_QUERY: &str = r#"(`todos`.`author_id` = ?
+    OR EXISTS (
+        SELECT 1 FROM `projects`
+            WHERE `projects`.`id` = `todos`.`project_id` AND `projects`.`author_id` = ?)
+    OR EXISTS (
+        SELECT 1 FROM `task_shares`
+            WHERE `task_shares`.`todo_id` = `todos`.`id` AND `task_shares`.`user_id` = ?
+            AND `task_shares`.`role` = 'owner')
+    OR EXISTS (
+        SELECT 1 FROM `project_shares`
+            WHERE `project_shares`.`project_id` = `todos`.`project_id`
+            AND `project_shares`.`user_id` = ? AND `project_shares`.`role` = 'owner')
+    OR EXISTS (
+        SELECT 1 FROM `workspace_members`
+            WHERE `workspace_members`.`workspace_id` = `todos`.`workspace_id`
+            AND `workspace_members`.`user_id` = ?
+            AND `workspace_members`.`role` IN ('owner', 'admin')))"#;
+pub const OWNED_TASK_BINDS: usize = 5;
+
+/// condition on `projects` matching the projects of the user and the ones shared with them,
+/// binding the user id twice
+pub const VISIBLE_PROJECT_QUERY: &str = r#"(`projects`.`author_id` = ?
+    OR EXISTS (
+        SELECT 1 FROM `project_shares`
+            WHERE `project_shares`.`project_id` = `projects`.`id`
+            AND `project_shares`.`user_id` = ?))"#;
+
+/// role of the user on the task: owner as its author or as the author of its project,
+/// otherwise the highest role shared with them on the task or on its project, or given by their
+/// role in its workspace, `None` without access
+///
+/// members of the workspace edit its tasks, admins and owners own them
+pub async fn get_task_role(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    task: &types::Todo,
+) -> anyhow::Result<Option<types::ShareRole>> {
+    let mut conn = conn.acquire().await?;
+
+    let bin_user_id = ulid_to_binary(user_id);
+    if task.author_id.as_deref() == Some(bin_user_id.as_slice()) {
+        return Ok(Some(types::ShareRole::Owner));
+    }
+
+    let query = r#"
+        SELECT `role` FROM `task_shares` WHERE `todo_id` = ? AND `user_id` = ?
+        UNION ALL
+        SELECT `role` FROM `project_shares` WHERE `project_id` = ? AND `user_id` = ?
+        UNION ALL
+        SELECT 'owner' FROM `projects` WHERE `id` = ? AND `author_id` = ?
+        UNION ALL
+        SELECT IF(`role` = 'member', 'editor', 'owner') FROM `workspace_members`
+            WHERE `workspace_id` = ? AND `user_id` = ?;"#;
+
+    let rows = sqlx::query(query)
+        .bind(task.id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .bind(task.project_id.as_deref())
+        .bind(bin_user_id.as_slice())
+        .bind(task.project_id.as_deref())
+        .bind(bin_user_id.as_slice())
+        .bind(task.workspace_id.as_deref())
+        .bind(bin_user_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let role = rows
+        .iter()
+        .map(|row| row.try_get::<types::ShareRole, _>(0))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .max();
+
+    Ok(role)
+}
+
+/// role of the user on the project: owner as its author, otherwise the role shared with them,
+/// `None` without access
+pub async fn get_project_role(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    project: &types::Project,
+) -> anyhow::Result<Option<types::ShareRole>> {
+    let mut conn = conn.acquire().await?;
+
+    let bin_user_id = ulid_to_binary(user_id);
+    if project.author_id == bin_user_id.as_slice() {
+        return Ok(Some(types::ShareRole::Owner));
+    }
+
+    let query = "SELECT `role` FROM `project_shares` WHERE `project_id` = ? AND `user_id` = ?;";
+
+    let row = sqlx::query(query)
+        .bind(project.id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    let role = row
+        .map(|row| row.try_get::<types::ShareRole, _>(0))
+        .transpose()?;
+
+    Ok(role)
+}
+
+/// sorted by username
+pub async fn get_task_shares(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Share>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT `task_shares`.`user_id`, `users`.`username`, `task_shares`.`role`, `task_shares`.`created_at`
+            FROM `task_shares`
+            INNER JOIN `users` ON `users`.`id` = `task_shares`.`user_id`
+            WHERE `task_shares`.`todo_id` = ?
+            ORDER BY `users`.`username` ASC;"#;
+
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let rows = sqlx::query_as::<_, types::Share>(query)
+        .bind(bin_task_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+/// sorted by username
+pub async fn get_project_shares(
+    conn: impl Acquire<'_, Database = MySql>,
+    project_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Share>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT `project_shares`.`user_id`, `users`.`username`, `project_shares`.`role`, `project_shares`.`created_at`
+            FROM `project_shares`
+            INNER JOIN `users` ON `users`.`id` = `project_shares`.`user_id`
+            WHERE `project_shares`.`project_id` = ?
+            ORDER BY `users`.`username` ASC;"#;
+
+    let bin_project_id = ulid_to_binary(project_id);
+
+    let rows = sqlx::query_as::<_, types::Share>(query)
+        .bind(bin_project_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+/// shares the task with the user, replacing the role they had
+pub async fn share_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+    role: types::ShareRole,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `task_shares` (`todo_id`, `user_id`, `role`) VALUES (?, ?, ?)
+            ON DUPLICATE KEY UPDATE `role` = VALUES(`role`);"#;
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .bind(role)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn unshare_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `task_shares` WHERE `todo_id` = ? AND `user_id` = ?;";
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// shares the project with the user, replacing the role they had
+pub async fn share_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    project_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+    role: types::ShareRole,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `project_shares` (`project_id`, `user_id`, `role`) VALUES (?, ?, ?)
+            ON DUPLICATE KEY UPDATE `role` = VALUES(`role`);"#;
+
+    let bin_project_id = ulid_to_binary(project_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_project_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .bind(role)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn unshare_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    project_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `project_shares` WHERE `project_id` = ? AND `user_id` = ?;";
+
+    let bin_project_id = ulid_to_binary(project_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_project_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// everyone with access to the task: its author and the users it or its project is shared with
+pub async fn get_task_member_ids(
+    conn: impl Acquire<'_, Database = MySql>,
+    task: &types::Todo,
+) -> anyhow::Result<Vec<ulid::Ulid>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT `user_id` FROM `task_shares` WHERE `todo_id` = ?
+        UNION
+        SELECT `user_id` FROM `project_shares` WHERE `project_id` = ?;"#;
+
+    let rows = sqlx::query(query)
+        .bind(task.id.as_slice())
+        .bind(task.project_id.as_deref())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut member_ids = task
+        .author_id
+        .as_deref()
+        .map(binary_to_ulid)
+        .transpose()?
+        .into_iter()
+        .collect::<Vec<_>>();
+    for row in rows {
+        let member_id = binary_to_ulid(row.try_get::<Vec<u8>, _>(0)?.as_slice())?;
+        if !member_ids.contains(&member_id) {
+            member_ids.push(member_id);
+        }
+    }
+
+    Ok(member_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Connection, Row};
+
+    use super::*;
+
+    #[test]
+    fn visible_task_query_binds_every_placeholder() {
+        assert_eq!(VISIBLE_TASK_QUERY.matches('?').count(), VISIBLE_TASK_BINDS);
+        assert_eq!(OWNED_TASK_QUERY.matches('?').count(), OWNED_TASK_BINDS);
+    }
+
+    /// `DATABASE_URL` points to a database created from `mysql/init/schema.sql`, everything
+    /// inserted is rolled back
+    #[actix_web::test]
+    #[ignore = "needs a MySQL database in DATABASE_URL"]
+    async fn project_owner_owns_tasks_of_collaborators() -> anyhow::Result<()> {
+        let mut conn = sqlx::MySqlConnection::connect(&std::env::var("DATABASE_URL")?).await?;
+        let mut tx = Connection::begin(&mut conn).await?;
+
+        let owner_ulid = ulid::Ulid::new();
+        let collaborator_ulid = ulid::Ulid::new();
+        let project_ulid = ulid::Ulid::new();
+        let task_ulid = ulid::Ulid::new();
+
+        for user_ulid in [owner_ulid, collaborator_ulid] {
+            sqlx::query(
+                "INSERT INTO `users` (`id`, `username`, `display_name`, `hashed_password`) VALUES (?, ?, '', '');",
+            )
+            .bind(ulid_to_binary(user_ulid).as_slice())
+            .bind(user_ulid.to_string())
+            .execute(&mut tx)
+            .await?;
+        }
+        sqlx::query(
+            "INSERT INTO `projects` (`id`, `author_id`, `name`, `description`) VALUES (?, ?, 'p', '');",
+        )
+        .bind(ulid_to_binary(project_ulid).as_slice())
+        .bind(ulid_to_binary(owner_ulid).as_slice())
+        .execute(&mut tx)
+        .await?;
+        sqlx::query("INSERT INTO `project_shares` (`project_id`, `user_id`, `role`) VALUES (?, ?, 'editor');")
+            .bind(ulid_to_binary(project_ulid).as_slice())
+            .bind(ulid_to_binary(collaborator_ulid).as_slice())
+            .execute(&mut tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO `todos` (`id`, `author_id`, `project_id`, `title`, `description`) VALUES (?, ?, ?, 't', '');",
+        )
+        .bind(ulid_to_binary(task_ulid).as_slice())
+        .bind(ulid_to_binary(collaborator_ulid).as_slice())
+        .bind(ulid_to_binary(project_ulid).as_slice())
+        .execute(&mut tx)
+        .await?;
+
+        let task = crate::model::tasks::get_task(&mut tx, task_ulid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        let role = get_task_role(&mut tx, owner_ulid, &task).await?;
+        assert_eq!(role, Some(types::ShareRole::Owner));
+
+        let query = format!(
+            "SELECT COUNT(*) FROM `todos` WHERE `todos`.`id` = ? AND {};",
+            VISIBLE_TASK_QUERY
+        );
+        let mut query = sqlx::query(&query).bind(ulid_to_binary(task_ulid).to_vec());
+        for _ in 0..VISIBLE_TASK_BINDS {
+            query = query.bind(ulid_to_binary(owner_ulid).to_vec());
+        }
+        let count = query.fetch_one(&mut tx).await?.get::<i64, _>(0);
+        assert_eq!(count, 1);
+
+        tx.rollback().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use sqlx::{mysql::MySqlArguments, Acquire, FromRow, MySql, Row};
+
+use super::Update;
+use crate::utils::{binary_to_ulid, ulid_to_binary};
+
+use super::types;
+
+/// personal tags of the user along with the tags of their workspaces
+pub async fn get_tags(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Tag>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `tags`
+            WHERE (`author_id` = ? AND `workspace_id` IS NULL)
+            OR `workspace_id` IN (
+                SELECT `workspace_id` FROM `workspace_members` WHERE `user_id` = ?)
+            ORDER BY `name` ASC;"#;
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let rows = sqlx::query_as::<_, types::Tag>(query)
+        .bind(bin_user_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_tag(
+    conn: impl Acquire<'_, Database = MySql>,
+    tag_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Tag>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `tags` WHERE `id` = ?;";
+
+    let bin_tag_id = ulid_to_binary(tag_id);
+
+    let row = sqlx::query_as::<_, types::Tag>(query)
+        .bind(bin_tag_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn get_tag_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    tag_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Tag>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `tags` WHERE `id` = ? FOR UPDATE;";
+
+    let bin_tag_id = ulid_to_binary(tag_id);
+
+    let row = sqlx::query_as::<_, types::Tag>(query)
+        .bind(bin_tag_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+/// among the tags of the workspace, or the personal tags of the author without one
+pub async fn is_tag_name_exists(
+    conn: impl Acquire<'_, Database = MySql>,
+    author_id: ulid::Ulid,
+    workspace_id: Option<ulid::Ulid>,
+    name: &str,
+) -> anyhow::Result<bool> {
+    let mut conn = conn.acquire().await?;
+
+    let query = if workspace_id.is_some() {
+        "SELECT COUNT(*) FROM `tags` WHERE `workspace_id` = ? AND `name` = ?;"
+    } else {
+        "SELECT COUNT(*) FROM `tags` WHERE `author_id` = ? AND `workspace_id` IS NULL AND `name` = ?;"
+    };
+
+    let bin_owner_id = ulid_to_binary(workspace_id.unwrap_or(author_id));
+
+    let count = sqlx::query(query)
+        .bind(bin_owner_id.as_slice())
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(count > 0)
+}
+
+pub async fn insert_tag(
+    conn: impl Acquire<'_, Database = MySql>,
+    tag: types::TagReq,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "INSERT INTO `tags` (`id`, `author_id`, `workspace_id`, `name`) VALUES (?, ?, ?, ?);";
+
+    sqlx::query(query)
+        .bind(tag.id)
+        .bind(tag.author_id)
+        .bind(tag.workspace_id)
+        .bind(tag.name)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateTag {
+    pub name: Update<String>,
+}
+impl UpdateTag {
+    fn to_prepared_query(&self) -> String {
+        let mut query = Vec::new();
+
+        if let Some(q) = self.name.to_prepared_query("name") {
+            query.push(q);
+        }
+
+        query.join(", ")
+    }
+
+    pub fn bind_query<'a>(
+        &'a self,
+        query: sqlx::query::Query<'a, sqlx::MySql, MySqlArguments>,
+    ) -> sqlx::query::Query<'a, sqlx::MySql, MySqlArguments> {
+        self.name.bind_query(query)
+    }
+
+    pub fn is_nop(&self) -> bool {
+        self.name.is_nop()
+    }
+}
+
+pub async fn update_tag(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    update: UpdateTag,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    if update.is_nop() {
+        return Ok(());
+    }
+
+    let query = format!(
+        "UPDATE `tags` SET {} WHERE `id` = ?;",
+        update.to_prepared_query()
+    );
+
+    let bin_id = ulid_to_binary(id);
+
+    let building_query = update
+        .bind_query(sqlx::query(query.as_str()))
+        .bind(bin_id.as_slice());
+
+    building_query.execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+pub async fn delete_tag(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `tags` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct Tagging {
+    todo_id: Vec<u8>,
+    #[sqlx(flatten)]
+    tag: types::Tag,
+}
+
+pub async fn get_tags_of_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_ids: &[ulid::Ulid],
+) -> anyhow::Result<HashMap<ulid::Ulid, Vec<types::Tag>>> {
+    let mut conn = conn.acquire().await?;
+
+    if task_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let query = format!(
+        r#"
+        SELECT `todo_taggings`.`todo_id`, `tags`.* FROM `todo_taggings`
+            INNER JOIN `tags` ON `tags`.`id` = `todo_taggings`.`tag_id`
+            WHERE `todo_taggings`.`todo_id` IN ({})
+            ORDER BY `tags`.`name` ASC;"#,
+        vec!["?"; task_ids.len()].join(", ")
+    );
+
+    let bin_task_ids = task_ids
+        .iter()
+        .map(|id| ulid_to_binary(*id))
+        .collect::<Vec<_>>();
+
+    let mut building_query = sqlx::query_as::<_, Tagging>(query.as_str());
+    for bin_task_id in bin_task_ids.iter() {
+        building_query = building_query.bind(bin_task_id.as_slice());
+    }
+
+    let rows = building_query.fetch_all(&mut *conn).await?;
+
+    let mut tags: HashMap<ulid::Ulid, Vec<types::Tag>> = HashMap::new();
+    for row in rows {
+        let todo_id = binary_to_ulid(row.todo_id.as_slice())?;
+        tags.entry(todo_id).or_default().push(row.tag);
+    }
+
+    Ok(tags)
+}
+
+pub async fn attach_tag(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    tag_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "INSERT IGNORE INTO `todo_taggings` (`todo_id`, `tag_id`) VALUES (?, ?);";
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_tag_id = ulid_to_binary(tag_id);
+
+    sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_tag_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn detach_tag(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+    tag_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `todo_taggings` WHERE `todo_id` = ? AND `tag_id` = ?;";
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_tag_id = ulid_to_binary(tag_id);
+
+    sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .bind(bin_tag_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
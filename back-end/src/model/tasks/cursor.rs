@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{mysql::MySqlArguments, Arguments};
+
+use super::{Order, SortedBy};
+use crate::{
+    model::types,
+    utils::{binary_to_ulid, ulid_to_binary},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    CreatedAt,
+    UpdatedAt,
+    Priority,
+    DueDateIsNull,
+    DueDate,
+    Title,
+    State,
+    Relevance,
+}
+impl SortKey {
+    const PRIORITY_CASE_QUERY: &'static str = r#"CASE
+            WHEN (`priority` = 'low') THEN 0
+            WHEN (`priority` IS NULL) THEN 1
+            WHEN (`priority` = 'medium') THEN 2
+            WHEN (`priority` = 'high') THEN 3
+            ELSE 4 END"#;
+    const STATE_CASE_QUERY: &'static str = r#"CASE
+            WHEN (`state` = 'icebox') THEN 0
+            WHEN (`state` = 'todo') THEN 1
+            WHEN (`state` = 'in-progress') THEN 2
+            WHEN (`state` = 'done') THEN 3
+            ELSE 4 END"#;
+
+    pub fn to_query(self) -> String {
+        match self {
+            SortKey::CreatedAt => "`created_at`".to_string(),
+            SortKey::UpdatedAt => "`updated_at`".to_string(),
+            SortKey::Priority => format!("({})", Self::PRIORITY_CASE_QUERY),
+            SortKey::DueDateIsNull => "(`due_date` IS NULL)".to_string(),
+            SortKey::DueDate => "`due_date`".to_string(),
+            SortKey::Title => "`title`".to_string(),
+            SortKey::State => format!("({})", Self::STATE_CASE_QUERY),
+            SortKey::Relevance => "`score`".to_string(),
+        }
+    }
+
+    /// the value `to_query` evaluates to for `task`
+    fn value_of(self, task: &types::Todo) -> CursorValue {
+        match self {
+            SortKey::CreatedAt => CursorValue::from_datetime(task.created_at),
+            SortKey::UpdatedAt => CursorValue::from_datetime(task.updated_at),
+            SortKey::Priority => CursorValue::Int(match task.priority {
+                Some(types::TaskPriority::Low) => 0,
+                None => 1,
+                Some(types::TaskPriority::Medium) => 2,
+                Some(types::TaskPriority::High) => 3,
+            }),
+            SortKey::DueDateIsNull => CursorValue::Int(task.due_date.is_none() as i64),
+            SortKey::DueDate => task
+                .due_date
+                .map(CursorValue::from_datetime)
+                .unwrap_or(CursorValue::Null),
+            SortKey::Title => CursorValue::Str(task.title.clone()),
+            SortKey::State => CursorValue::Int(match task.state {
+                types::TaskState::Icebox => 0,
+                types::TaskState::Todo => 1,
+                types::TaskState::InProgress => 2,
+                types::TaskState::Done => 3,
+            }),
+            SortKey::Relevance => CursorValue::Float(task.score.unwrap_or_default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+enum CursorValue {
+    Int(i64),
+    Float(f64),
+    DateTime(String),
+    Str(String),
+    Null,
+}
+impl CursorValue {
+    const DATETIME_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S";
+
+    fn from_datetime(datetime: chrono::NaiveDateTime) -> Self {
+        CursorValue::DateTime(datetime.format(Self::DATETIME_FORMAT).to_string())
+    }
+
+    fn add_argument(&self, args: &mut MySqlArguments) -> anyhow::Result<()> {
+        match self {
+            CursorValue::Int(i) => args.add(*i),
+            CursorValue::Float(f) => args.add(*f),
+            CursorValue::DateTime(s) => args.add(chrono::NaiveDateTime::parse_from_str(
+                s,
+                Self::DATETIME_FORMAT,
+            )?),
+            CursorValue::Str(s) => args.add(s.clone()),
+            CursorValue::Null => args.add(Option::<i64>::None),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// rows after the cursor
+    Next,
+    /// rows before the cursor
+    Prev,
+}
+
+/// position of a row in a sorted task list, handed to clients as an opaque string
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cursor {
+    direction: Direction,
+    keys: Vec<SortKey>,
+    values: Vec<CursorValue>,
+    id: String,
+}
+impl Cursor {
+    pub fn new(
+        direction: Direction,
+        sorted_by: &SortedBy,
+        task: &types::Todo,
+    ) -> anyhow::Result<Self> {
+        let keys = sorted_by
+            .keys()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        let values = keys.iter().map(|key| key.value_of(task)).collect();
+        let id = binary_to_ulid(task.id.as_slice())?.to_string();
+
+        Ok(Self {
+            direction,
+            keys,
+            values,
+            id,
+        })
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn encode(&self) -> anyhow::Result<String> {
+        let json = serde_json::to_vec(self)?;
+        Ok(base64::encode_config(json, base64::URL_SAFE_NO_PAD))
+    }
+
+    /// fails when the cursor was not issued for `sorted_by`
+    pub fn decode(s: &str, sorted_by: &SortedBy) -> anyhow::Result<Self> {
+        let json = base64::decode_config(s, base64::URL_SAFE_NO_PAD)?;
+        let cursor: Self = serde_json::from_slice(&json)?;
+
+        let keys = sorted_by
+            .keys()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        if cursor.keys != keys || cursor.values.len() != keys.len() {
+            anyhow::bail!("Cursor does not match the sort");
+        }
+        ulid::Ulid::from_string(&cursor.id)?;
+
+        Ok(cursor)
+    }
+
+    /// condition selecting the rows beyond the cursor in its direction
+    ///
+    /// `(k1 > v1) OR (k1 <=> v1 AND k2 > v2) OR ... OR (k1 <=> v1 AND ... AND id > v_id)`
+    pub fn to_prepared_query(&self, sorted_by: &SortedBy) -> String {
+        let mut columns = sorted_by
+            .keys()
+            .into_iter()
+            .map(|(key, order)| (key.to_query(), order))
+            .collect::<Vec<_>>();
+        columns.push(("`id`".to_string(), sorted_by.id_order()));
+
+        let branches = (0..columns.len())
+            .map(|i| {
+                let mut conditions = columns[..i]
+                    .iter()
+                    .map(|(column, _)| format!("{} <=> ?", column))
+                    .collect::<Vec<_>>();
+                let (column, order) = &columns[i];
+                let op = match (order, self.direction) {
+                    (Order::Asc, Direction::Next) | (Order::Desc, Direction::Prev) => ">",
+                    (Order::Desc, Direction::Next) | (Order::Asc, Direction::Prev) => "<",
+                };
+                conditions.push(format!("{} {} ?", column, op));
+                format!("({})", conditions.join(" AND "))
+            })
+            .collect::<Vec<_>>();
+
+        format!("({})", branches.join(" OR "))
+    }
+
+    pub fn add_arguments(&self, args: &mut MySqlArguments) -> anyhow::Result<()> {
+        let bin_id = ulid_to_binary(ulid::Ulid::from_string(&self.id)?);
+
+        for i in 0..=self.values.len() {
+            for value in self.values[..i].iter() {
+                value.add_argument(args)?;
+            }
+            match self.values.get(i) {
+                Some(value) => value.add_argument(args)?,
+                None => args.add(bin_id.to_vec()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(direction: Direction, sorted_by: &SortedBy) -> Cursor {
+        Cursor {
+            direction,
+            keys: sorted_by.keys().into_iter().map(|(key, _)| key).collect(),
+            values: vec![
+                CursorValue::Int(2),
+                CursorValue::DateTime("2026-10-18 09:30:00".into()),
+            ],
+            id: ulid::Ulid::nil().to_string(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let sorted_by = SortedBy::PriorityAndCreatedAt(Order::Desc, Order::Asc);
+        let encoded = cursor(Direction::Prev, &sorted_by).encode().unwrap();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        let decoded = Cursor::decode(&encoded, &sorted_by).unwrap();
+        assert_eq!(decoded.direction(), Direction::Prev);
+        assert_eq!(decoded.encode().unwrap(), encoded);
+    }
+
+    #[test]
+    fn rejects_another_sort() {
+        let sorted_by = SortedBy::PriorityAndCreatedAt(Order::Desc, Order::Desc);
+        let encoded = cursor(Direction::Next, &sorted_by).encode().unwrap();
+
+        assert!(Cursor::decode(&encoded, &SortedBy::Priority(Order::Desc)).is_err());
+        assert!(Cursor::decode(
+            &encoded,
+            &SortedBy::PriorityAndUpdatedAt(Order::Desc, Order::Desc)
+        )
+        .is_err());
+        // the orders are not part of the cursor
+        assert!(Cursor::decode(
+            &encoded,
+            &SortedBy::PriorityAndCreatedAt(Order::Asc, Order::Asc)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let sorted_by = SortedBy::PriorityAndCreatedAt(Order::Desc, Order::Desc);
+        let encode = |json: &str| base64::encode_config(json, base64::URL_SAFE_NO_PAD);
+
+        for s in [
+            "",
+            "not base64!",
+            &encode("not json"),
+            &encode(
+                r#"{"direction":"next","keys":["priority","created_at"],"values":[],"id":"01"}"#,
+            ),
+            &encode(&format!(
+                r#"{{"direction":"next","keys":["priority","created_at"],"values":[{{"type":"int","value":2}},{{"type":"null"}}],"id":"{}"}}"#,
+                "not a ulid"
+            )),
+        ] {
+            assert!(Cursor::decode(s, &sorted_by).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn prepared_query_follows_the_direction() {
+        let sorted_by = SortedBy::PriorityAndCreatedAt(Order::Desc, Order::Asc);
+
+        assert_eq!(
+            cursor(Direction::Next, &sorted_by).to_prepared_query(&sorted_by),
+            format!(
+                "(({p} < ?) OR ({p} <=> ? AND `created_at` > ?) \
+                 OR ({p} <=> ? AND `created_at` <=> ? AND `id` > ?))",
+                p = SortKey::Priority.to_query()
+            )
+        );
+        assert_eq!(
+            cursor(Direction::Prev, &SortedBy::CreatedAt(Order::Desc))
+                .to_prepared_query(&SortedBy::CreatedAt(Order::Desc)),
+            "((`created_at` > ?) OR (`created_at` <=> ? AND `id` > ?))"
+        );
+    }
+}
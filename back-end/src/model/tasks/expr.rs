@@ -0,0 +1,665 @@
+use std::{fmt, iter::Peekable, str::FromStr, vec::IntoIter};
+
+use chrono::Datelike;
+
+use sqlx::{mysql::MySqlArguments, Arguments};
+
+use super::TaskFilter;
+use crate::model::types;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// char offset in the input
+    pub position: usize,
+    pub message: String,
+}
+impl ParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    Due,
+    Created,
+    Updated,
+}
+impl DateField {
+    fn column(self) -> &'static str {
+        match self {
+            DateField::Due => "`todos`.`due_date`",
+            DateField::Created => "`todos`.`created_at`",
+            DateField::Updated => "`todos`.`updated_at`",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateCondition {
+    Before(chrono::NaiveDateTime),
+    Until(chrono::NaiveDateTime),
+    After(chrono::NaiveDateTime),
+    Since(chrono::NaiveDateTime),
+    /// from the start of the day until the start of the next one
+    On(chrono::NaiveDateTime, chrono::NaiveDateTime),
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    State(Vec<types::TaskState>),
+    /// `None` matches tasks without priority
+    Priority(Vec<Option<types::TaskPriority>>),
+    Date(DateField, DateCondition),
+    /// tagged with any of the tag names
+    Tag(Vec<String>),
+    Overdue,
+    /// substring of the title or the description
+    Text(String),
+}
+
+/// filter written as `state:todo,in-progress priority:high due:<2026-11-01 tag:work "release notes"`
+///
+/// terms separated by spaces must all match; `OR`, `-` for negation and parentheses are also
+/// available
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Condition(Condition),
+}
+impl FromStr for FilterExpr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        if tokens.is_empty() {
+            return Ok(FilterExpr::And(Vec::new()));
+        }
+        let end = s.chars().count();
+        let mut parser = Parser {
+            tokens: tokens.into_iter().peekable(),
+            end,
+            depth: 0,
+        };
+
+        let expr = parser.parse_or()?;
+        match parser.tokens.next() {
+            Some(token) => Err(ParseError::new(token.position, "Unexpected `)`")),
+            None => Ok(expr),
+        }
+    }
+}
+impl FilterExpr {
+    pub fn to_prepared_query(&self) -> String {
+        match self {
+            FilterExpr::And(exprs) if exprs.is_empty() => "TRUE".to_string(),
+            FilterExpr::And(exprs) => Self::join(exprs, " AND "),
+            FilterExpr::Or(exprs) => Self::join(exprs, " OR "),
+            // tasks where the inner condition is unknown because of a NULL also count as not
+            // matching
+            FilterExpr::Not(expr) => format!("(({}) IS NOT TRUE)", expr.to_prepared_query()),
+            FilterExpr::Condition(condition) => condition.to_prepared_query(),
+        }
+    }
+
+    fn join(exprs: &[FilterExpr], separator: &str) -> String {
+        let query = exprs
+            .iter()
+            .map(|e| e.to_prepared_query())
+            .collect::<Vec<_>>()
+            .join(separator);
+        format!("({})", query)
+    }
+
+    pub fn add_arguments(&self, args: &mut MySqlArguments) {
+        match self {
+            FilterExpr::And(exprs) | FilterExpr::Or(exprs) => {
+                for expr in exprs {
+                    expr.add_arguments(args);
+                }
+            }
+            FilterExpr::Not(expr) => expr.add_arguments(args),
+            FilterExpr::Condition(condition) => condition.add_arguments(args),
+        }
+    }
+}
+
+impl Condition {
+    fn to_prepared_query(&self) -> String {
+        match self {
+            Condition::State(state) => TaskFilter::in_query("`todos`.`state`", state.len()),
+            Condition::Priority(priority) => TaskFilter::priority_query(priority),
+            Condition::Date(field, condition) => {
+                let column = field.column();
+                match condition {
+                    DateCondition::Before(_) => format!("{} < ?", column),
+                    DateCondition::Until(_) => format!("{} <= ?", column),
+                    DateCondition::After(_) => format!("{} > ?", column),
+                    DateCondition::Since(_) => format!("{} >= ?", column),
+                    DateCondition::On(..) => format!("({} >= ? AND {} < ?)", column, column),
+                    DateCondition::None => format!("{} IS NULL", column),
+                }
+            }
+            Condition::Tag(names) => format!(
+                r#"EXISTS (
+                    SELECT 1 FROM `todo_taggings`
+                        INNER JOIN `tags` ON `tags`.`id` = `todo_taggings`.`tag_id`
+                        WHERE `todo_taggings`.`todo_id` = `todos`.`id`
+                        AND {})"#,
+                TaskFilter::in_query("`tags`.`name`", names.len())
+            ),
+            Condition::Overdue => TaskFilter::OVERDUE_QUERY.to_string(),
+            Condition::Text(_) => {
+                "(`todos`.`title` LIKE ? OR `todos`.`description` LIKE ?)".to_string()
+            }
+        }
+    }
+
+    fn add_arguments(&self, args: &mut MySqlArguments) {
+        match self {
+            Condition::State(state) => {
+                for s in state {
+                    args.add(*s);
+                }
+            }
+            Condition::Priority(priority) => {
+                for p in priority.iter().flatten() {
+                    args.add(*p);
+                }
+            }
+            Condition::Date(_, condition) => match condition {
+                DateCondition::Before(date)
+                | DateCondition::Until(date)
+                | DateCondition::After(date)
+                | DateCondition::Since(date) => args.add(*date),
+                DateCondition::On(start, end) => {
+                    args.add(*start);
+                    args.add(*end);
+                }
+                DateCondition::None => (),
+            },
+            Condition::Tag(names) => {
+                for name in names {
+                    args.add(name.clone());
+                }
+            }
+            Condition::Overdue => (),
+            Condition::Text(text) => {
+                let text = format!("%{}%", TaskFilter::escape_like(text));
+                args.add(text.clone());
+                args.add(text);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Minus,
+    Or,
+    /// `key:value`, or a bare word or a quoted phrase without key
+    Term {
+        key: Option<String>,
+        value: String,
+        value_position: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().enumerate().peekable();
+
+    let read_quoted = |chars: &mut Peekable<std::iter::Enumerate<std::str::Chars>>,
+                       position: usize|
+     -> Result<String, ParseError> {
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some((_, '"')) => return Ok(value),
+                Some((_, c)) => value.push(c),
+                None => return Err(ParseError::new(position, "Unterminated quote")),
+            }
+        }
+    };
+
+    while let Some(&(position, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::LParen,
+                    position,
+                });
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::RParen,
+                    position,
+                });
+            }
+            '-' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::Minus,
+                    position,
+                });
+            }
+            '"' => {
+                chars.next();
+                let value = read_quoted(&mut chars, position)?;
+                tokens.push(Token {
+                    kind: TokenKind::Term {
+                        key: None,
+                        value,
+                        value_position: position + 1,
+                    },
+                    position,
+                });
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))
+                {
+                    word.push(c);
+                    if c == ':' {
+                        break;
+                    }
+                }
+
+                let kind = match word.strip_suffix(':') {
+                    Some(key) => {
+                        let value_position = position + word.chars().count();
+                        let value = match chars.peek() {
+                            Some(&(quote_position, '"')) => {
+                                chars.next();
+                                read_quoted(&mut chars, quote_position)?
+                            }
+                            _ => {
+                                let mut value = String::new();
+                                while let Some((_, c)) = chars.next_if(|(_, c)| {
+                                    !c.is_whitespace() && !matches!(c, '(' | ')' | '"')
+                                }) {
+                                    value.push(c);
+                                }
+                                value
+                            }
+                        };
+                        if value.is_empty() {
+                            return Err(ParseError::new(value_position, "Missing value"));
+                        }
+                        TokenKind::Term {
+                            key: Some(key.to_lowercase()),
+                            value,
+                            value_position,
+                        }
+                    }
+                    None if word == "OR" => TokenKind::Or,
+                    None => TokenKind::Term {
+                        key: None,
+                        value: word,
+                        value_position: position,
+                    },
+                };
+                tokens.push(Token { kind, position });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Peekable<IntoIter<Token>>,
+    /// char length of the input, reported for errors at the end
+    end: usize,
+    /// parentheses and negations the current term is nested in
+    depth: usize,
+}
+impl Parser {
+    /// keeps the recursion off the end of the stack
+    const MAX_DEPTH: usize = 32;
+
+    fn parse_or(&mut self) -> Result<FilterExpr, ParseError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self
+            .tokens
+            .next_if(|t| matches!(t.kind, TokenKind::Or))
+            .is_some()
+        {
+            exprs.push(self.parse_and()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            FilterExpr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, ParseError> {
+        let mut exprs = Vec::new();
+        while let Some(token) = self.tokens.peek() {
+            match token.kind {
+                TokenKind::RParen => break,
+                TokenKind::Or if exprs.is_empty() => {
+                    return Err(ParseError::new(token.position, "Unexpected `OR`"));
+                }
+                TokenKind::Or => break,
+                _ => exprs.push(self.parse_unary()?),
+            }
+        }
+
+        match exprs.len() {
+            0 => {
+                let position = self.tokens.peek().map_or(self.end, |t| t.position);
+                Err(ParseError::new(position, "Expected a term"))
+            }
+            1 => Ok(exprs.pop().unwrap()),
+            _ => Ok(FilterExpr::And(exprs)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, ParseError> {
+        let token = self
+            .tokens
+            .next()
+            .ok_or_else(|| ParseError::new(self.end, "Unexpected end"))?;
+
+        if matches!(token.kind, TokenKind::Minus | TokenKind::LParen) {
+            if self.depth >= Self::MAX_DEPTH {
+                return Err(ParseError::new(token.position, "Too deeply nested"));
+            }
+            self.depth += 1;
+        }
+
+        let expr = match token.kind {
+            TokenKind::Minus => FilterExpr::Not(Box::new(self.parse_unary()?)),
+            TokenKind::LParen => {
+                let expr = self.parse_or()?;
+                match self.tokens.next() {
+                    Some(Token {
+                        kind: TokenKind::RParen,
+                        ..
+                    }) => expr,
+                    _ => return Err(ParseError::new(token.position, "Unclosed `(`")),
+                }
+            }
+            TokenKind::Term {
+                key,
+                value,
+                value_position,
+            } => {
+                return Ok(FilterExpr::Condition(parse_condition(
+                    key.as_deref(),
+                    &value,
+                    token.position,
+                    value_position,
+                )?))
+            }
+            TokenKind::RParen | TokenKind::Or => {
+                return Err(ParseError::new(token.position, "Unexpected token"))
+            }
+        };
+        self.depth -= 1;
+
+        Ok(expr)
+    }
+}
+
+fn parse_condition(
+    key: Option<&str>,
+    value: &str,
+    position: usize,
+    value_position: usize,
+) -> Result<Condition, ParseError> {
+    let invalid = |message: &str| ParseError::new(value_position, message);
+    let list = || value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty());
+
+    let condition = match key {
+        None => Condition::Text(value.to_string()),
+        Some("state") => Condition::State(
+            list()
+                .map(|v| types::TaskState::from_str(v).map_err(|_| invalid("Invalid state")))
+                .collect::<Result<_, _>>()?,
+        ),
+        Some("priority") => Condition::Priority(
+            list()
+                .map(|v| match v {
+                    "none" => Ok(None),
+                    v => types::TaskPriority::from_str(v)
+                        .map(Some)
+                        .map_err(|_| invalid("Invalid priority")),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        Some("due") => {
+            Condition::Date(DateField::Due, parse_date_condition(value, value_position)?)
+        }
+        Some("created") => Condition::Date(
+            DateField::Created,
+            parse_date_condition(value, value_position)?,
+        ),
+        Some("updated") => Condition::Date(
+            DateField::Updated,
+            parse_date_condition(value, value_position)?,
+        ),
+        Some("tag") => Condition::Tag(list().map(|v| v.to_string()).collect()),
+        Some("is") => match value {
+            "overdue" => Condition::Overdue,
+            _ => return Err(invalid("Unknown `is:` value")),
+        },
+        Some(_) => return Err(ParseError::new(position, "Unknown key")),
+    };
+
+    Ok(condition)
+}
+
+fn parse_date_condition(value: &str, value_position: usize) -> Result<DateCondition, ParseError> {
+    if value == "none" {
+        return Ok(DateCondition::None);
+    }
+
+    let (operator, date) = ["<=", ">=", "<", ">"]
+        .iter()
+        .find_map(|op| value.strip_prefix(op).map(|date| (*op, date)))
+        .unwrap_or(("", value));
+    let date_position = value_position + operator.chars().count();
+
+    let datetime = chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .map(|d| d.and_hms(0, 0, 0))
+        })
+        // MySQL only stores the years 1000 to 9999
+        .filter(|d| (1000..=9999).contains(&d.year()))
+        .ok_or_else(|| ParseError::new(date_position, "Invalid date"))?;
+
+    Ok(match operator {
+        "<" => DateCondition::Before(datetime),
+        "<=" => DateCondition::Until(datetime),
+        ">" => DateCondition::After(datetime),
+        ">=" => DateCondition::Since(datetime),
+        _ => {
+            let date = datetime.date();
+            let next = date
+                .succ_opt()
+                .filter(|d| d.year() <= 9999)
+                .ok_or_else(|| ParseError::new(date_position, "Invalid date"))?;
+            DateCondition::On(date.and_hms(0, 0, 0), next.and_hms(0, 0, 0))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(q: &str) -> FilterExpr {
+        FilterExpr::from_str(q).unwrap()
+    }
+
+    fn error(q: &str) -> (usize, String) {
+        let e = FilterExpr::from_str(q).unwrap_err();
+        (e.position, e.message)
+    }
+
+    #[test]
+    fn terms_and_lists() {
+        assert_eq!(
+            parse(r#"state:todo,in-progress priority:high,none tag:"to do" "release notes""#),
+            FilterExpr::And(vec![
+                FilterExpr::Condition(Condition::State(vec![
+                    types::TaskState::Todo,
+                    types::TaskState::InProgress
+                ])),
+                FilterExpr::Condition(Condition::Priority(vec![
+                    Some(types::TaskPriority::High),
+                    None
+                ])),
+                FilterExpr::Condition(Condition::Tag(vec!["to do".to_string()])),
+                FilterExpr::Condition(Condition::Text("release notes".to_string())),
+            ])
+        );
+        assert_eq!(parse("  "), FilterExpr::And(Vec::new()));
+        assert_eq!(
+            parse("DUE:none is:overdue"),
+            FilterExpr::And(vec![
+                FilterExpr::Condition(Condition::Date(DateField::Due, DateCondition::None)),
+                FilterExpr::Condition(Condition::Overdue),
+            ])
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        let text = |t: &str| FilterExpr::Condition(Condition::Text(t.to_string()));
+        // `OR` binds looser than the implicit `AND`, `-` tighter
+        assert_eq!(
+            parse("a b OR -c"),
+            FilterExpr::Or(vec![
+                FilterExpr::And(vec![text("a"), text("b")]),
+                FilterExpr::Not(Box::new(text("c"))),
+            ])
+        );
+        assert_eq!(
+            parse("a (b OR c)"),
+            FilterExpr::And(vec![text("a"), FilterExpr::Or(vec![text("b"), text("c")]),])
+        );
+    }
+
+    #[test]
+    fn prepared_query_matches_arguments() {
+        let expr = parse(
+            "state:todo,done -(priority:none,high OR tag:a,b) due:2026-11-01 created:<2026-01-01 \
+             updated:>=2026-01-01 is:overdue word",
+        );
+        let query = expr.to_prepared_query();
+        assert!(query.starts_with("(`todos`.`state` IN (?, ?) AND ((((`todos`.`priority` IN (?)"));
+        assert!(query.contains(") IS NOT TRUE)"));
+
+        // 2 states, 1 priority, 2 tags, 2 for the day, 1 + 1 dates and the text twice
+        assert_eq!(query.matches('?').count(), 11);
+        expr.add_arguments(&mut MySqlArguments::default());
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(FilterExpr::from_str(&nested(Parser::MAX_DEPTH)).is_ok());
+        assert_eq!(
+            error(&nested(100_000)),
+            (Parser::MAX_DEPTH, "Too deeply nested".to_string())
+        );
+
+        let negated = |depth: usize| format!("{}a", "-".repeat(depth));
+        assert!(FilterExpr::from_str(&negated(Parser::MAX_DEPTH)).is_ok());
+        assert_eq!(
+            error(&format!("b {}", negated(100_000))),
+            (Parser::MAX_DEPTH + 2, "Too deeply nested".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}{}", "-(".repeat(20), "a")),
+            (Parser::MAX_DEPTH, "Too deeply nested".to_string())
+        );
+    }
+
+    #[test]
+    fn error_positions() {
+        for (q, position, message) in [
+            ("state:todo)", 10, "Unexpected `)`"),
+            ("(state:todo", 0, "Unclosed `(`"),
+            ("a OR", 4, "Expected a term"),
+            ("OR a", 0, "Unexpected `OR`"),
+            ("a -", 3, "Unexpected end"),
+            ("()", 1, "Expected a term"),
+            (r#"a "bc"#, 2, "Unterminated quote"),
+            (r#"tag:"x"#, 4, "Unterminated quote"),
+            ("a due: b", 6, "Missing value"),
+            ("state:todo,later", 6, "Invalid state"),
+            ("priority:urgent", 9, "Invalid priority"),
+            ("is:late", 3, "Unknown `is:` value"),
+            ("a colour:red", 2, "Unknown key"),
+            ("due:>=2026-13-01", 6, "Invalid date"),
+            ("créé due:x", 9, "Invalid date"),
+        ] {
+            assert_eq!(error(q), (position, message.to_string()), "{}", q);
+        }
+    }
+
+    #[test]
+    fn dates_out_of_range() {
+        for (q, position) in [
+            ("due:+262143-12-31", 4),
+            ("due:<=+262143-12-31", 6),
+            ("created:>=0999-12-31", 10),
+            ("updated:10000-01-01", 8),
+        ] {
+            assert_eq!(
+                FilterExpr::from_str(q),
+                Err(ParseError::new(position, "Invalid date")),
+                "{}",
+                q
+            );
+        }
+    }
+
+    #[test]
+    fn date_on_spans_the_day() {
+        let day = |d| chrono::NaiveDate::from_ymd(9999, 12, d).and_hms(0, 0, 0);
+        assert_eq!(
+            FilterExpr::from_str("due:9999-12-30"),
+            Ok(FilterExpr::Condition(Condition::Date(
+                DateField::Due,
+                DateCondition::On(day(30), day(31))
+            )))
+        );
+        assert!(FilterExpr::from_str("due:9999-12-31").is_err());
+    }
+}
@@ -1,12 +1,24 @@
-use std::{fmt::Display, str::FromStr};
+mod cursor;
+mod expr;
+mod search;
 
-use sqlx::{mysql::MySqlArguments, Acquire, MySql};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use sqlx::{mysql::MySqlArguments, Acquire, Arguments, FromRow, MySql, Row};
 
 use super::{types::VecWithTotal, Update};
-use crate::utils::ulid_to_binary;
+use crate::utils::{binary_to_ulid, ulid_to_binary};
 
 use super::types;
 
+use cursor::SortKey;
+pub use cursor::{Cursor, Direction};
+pub use expr::FilterExpr;
+pub use search::{SearchMatch, SearchQuery};
+
 #[derive(Debug, Clone, Copy)]
 pub enum Order {
     Asc,
@@ -19,6 +31,13 @@ impl Order {
             Order::Desc => "DESC".to_string(),
         }
     }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Order::Asc => Order::Desc,
+            Order::Desc => Order::Asc,
+        }
+    }
 }
 impl FromStr for Order {
     type Err = anyhow::Error;
@@ -38,137 +57,580 @@ pub enum SortedBy {
     Priority(Order),
     PriorityAndCreatedAt(Order, Order),
     PriorityAndUpdatedAt(Order, Order),
+    /// tasks without a due date always come last
+    DueDate(Order),
+    Title(Order),
+    State(Order),
+    /// only available while searching
+    Relevance(Order),
 }
 impl SortedBy {
-    const PRIORITY_CASE_QUERY: &'static str = r#"CASE
-            WHEN (`priority` = 'low') THEN 0
-            WHEN (`priority` IS NULL) THEN 1
-            WHEN (`priority` = 'medium') THEN 2
-            WHEN (`priority` = 'high') THEN 3
-            ELSE 4 END"#;
-
-    pub fn to_query(&self) -> String {
-        let mut query = Vec::new();
-        query.push("ORDER BY".to_string());
+    /// builds from `sort` / `order` query values such as `priority,created_at` / `desc,asc`
+    ///
+    /// omitted orders fall back to the natural one of each key
+    pub fn from_query(sort: &str, order: Option<&str>) -> anyhow::Result<Self> {
+        let keys = sort.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+        let orders = order
+            .map(|o| {
+                o.split(',')
+                    .map(|s| Order::from_str(s.trim()))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        if orders.len() > keys.len() {
+            anyhow::bail!("Too many orders");
+        }
+        let order_at = |i: usize, default: Order| orders.get(i).copied().unwrap_or(default);
 
-        match self {
-            SortedBy::CreatedAt(order) => {
-                query.push(format!("`created_at` {}", order.to_query()));
-            }
-            SortedBy::UpdatedAt(order) => {
-                query.push(format!("`updated_at` {}", order.to_query()));
+        let sorted_by = match keys.as_slice() {
+            ["created_at"] => SortedBy::CreatedAt(order_at(0, Order::Desc)),
+            ["updated_at"] => SortedBy::UpdatedAt(order_at(0, Order::Desc)),
+            ["priority"] => SortedBy::Priority(order_at(0, Order::Desc)),
+            ["priority", "created_at"] => {
+                SortedBy::PriorityAndCreatedAt(order_at(0, Order::Desc), order_at(1, Order::Desc))
             }
-            SortedBy::Priority(order)
-            | SortedBy::PriorityAndCreatedAt(order, _)
-            | SortedBy::PriorityAndUpdatedAt(order, _) => {
-                query.push(format!(
-                    "{} {}",
-                    Self::PRIORITY_CASE_QUERY,
-                    order.to_query()
-                ));
-                match self {
-                    SortedBy::PriorityAndCreatedAt(_, order) => {
-                        query.push(format!(", `created_at` {}", order.to_query()));
-                    }
-                    SortedBy::PriorityAndUpdatedAt(_, order) => {
-                        query.push(format!(", `updated_at` {}", order.to_query()));
-                    }
-                    SortedBy::Priority(_) => {}
-                    _ => unreachable!(),
-                }
+            ["priority", "updated_at"] => {
+                SortedBy::PriorityAndUpdatedAt(order_at(0, Order::Desc), order_at(1, Order::Desc))
             }
+            ["due_date"] => SortedBy::DueDate(order_at(0, Order::Asc)),
+            ["title"] => SortedBy::Title(order_at(0, Order::Asc)),
+            ["state"] => SortedBy::State(order_at(0, Order::Asc)),
+            ["relevance"] => SortedBy::Relevance(order_at(0, Order::Desc)),
+            _ => anyhow::bail!("Invalid sort"),
+        };
+
+        Ok(sorted_by)
+    }
+
+    /// sort keys in priority order, without the trailing `id` tie-breaker
+    pub fn keys(&self) -> Vec<(SortKey, Order)> {
+        match self {
+            SortedBy::CreatedAt(order) => vec![(SortKey::CreatedAt, *order)],
+            SortedBy::UpdatedAt(order) => vec![(SortKey::UpdatedAt, *order)],
+            SortedBy::Priority(order) => vec![(SortKey::Priority, *order)],
+            SortedBy::PriorityAndCreatedAt(priority_order, order) => vec![
+                (SortKey::Priority, *priority_order),
+                (SortKey::CreatedAt, *order),
+            ],
+            SortedBy::PriorityAndUpdatedAt(priority_order, order) => vec![
+                (SortKey::Priority, *priority_order),
+                (SortKey::UpdatedAt, *order),
+            ],
+            SortedBy::DueDate(order) => vec![
+                (SortKey::DueDateIsNull, Order::Asc),
+                (SortKey::DueDate, *order),
+            ],
+            SortedBy::Title(order) => vec![(SortKey::Title, *order)],
+            SortedBy::State(order) => vec![(SortKey::State, *order)],
+            SortedBy::Relevance(order) => vec![(SortKey::Relevance, *order)],
         }
+    }
+
+    /// ties are broken by `id` so that the order is total
+    pub fn id_order(&self) -> Order {
+        self.keys()
+            .last()
+            .map(|(_, order)| *order)
+            .unwrap_or(Order::Desc)
+    }
+
+    pub fn to_query(&self) -> String {
+        self.to_query_with(false)
+    }
+
+    pub fn to_reversed_query(&self) -> String {
+        self.to_query_with(true)
+    }
+
+    fn to_query_with(&self, reversed: bool) -> String {
+        let mut columns = self
+            .keys()
+            .into_iter()
+            .map(|(key, order)| (key.to_query(), order))
+            .collect::<Vec<_>>();
+        columns.push(("`id`".to_string(), self.id_order()));
 
-        query.join(" ")
+        let columns = columns
+            .into_iter()
+            .map(|(column, order)| {
+                let order = if reversed { order.reverse() } else { order };
+                format!("{} {}", column, order.to_query())
+            })
+            .collect::<Vec<_>>();
+
+        format!("ORDER BY {}", columns.join(", "))
+    }
+}
+impl Default for SortedBy {
+    fn default() -> Self {
+        SortedBy::CreatedAt(Order::Desc)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Limit {
     LimitOffset(usize, usize),
+    /// first page of a keyset pagination
     Limit(usize),
+    Cursor(usize, Cursor),
 }
 impl Limit {
-    pub fn to_query(self) -> String {
+    pub fn to_prepared_query(&self) -> String {
         match self {
-            Self::LimitOffset(limit, offset) => format!("LIMIT {} OFFSET {}", limit, offset),
-            Self::Limit(limit) => format!("LIMIT {}", limit),
+            Self::LimitOffset(_, _) => "LIMIT ? OFFSET ?".to_string(),
+            Self::Limit(_) | Self::Cursor(_, _) => "LIMIT ?".to_string(),
         }
     }
 
-    pub fn to_prepared_query(self) -> String {
+    fn add_arguments(&self, args: &mut MySqlArguments) {
         match self {
-            Self::LimitOffset(_, _) => "LIMIT ? OFFSET ?".to_string(),
-            Self::Limit(_) => "LIMIT ?".to_string(),
+            Self::LimitOffset(limit, offset) => {
+                args.add(*limit as i64);
+                args.add(*offset as i64);
+            }
+            // one extra row tells whether another page follows
+            Self::Limit(limit) | Self::Cursor(limit, _) => args.add(*limit as i64 + 1),
         }
     }
 }
 
+/// which project the tasks belong to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectFilter {
+    /// tasks without a project
+    Inbox,
+    Project(ulid::Ulid),
+}
+
+/// which workspace the tasks belong to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceFilter {
+    /// tasks outside of any workspace
+    Personal,
+    Workspace(ulid::Ulid),
+}
+
+/// whether archived tasks are listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArchivedFilter {
+    #[default]
+    Exclude,
+    Include,
+    Only,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    /// substring of the title or the description
+    pub phrase: Option<String>,
+    pub search: Option<SearchQuery>,
+    pub state: Option<Vec<types::TaskState>>,
+    /// `None` matches tasks without priority
+    pub priority: Option<Vec<Option<types::TaskPriority>>>,
+    pub due_before: Option<chrono::NaiveDateTime>,
+    pub due_after: Option<chrono::NaiveDateTime>,
+    /// not done yet and past the due date
+    pub overdue: Option<bool>,
+    pub created_before: Option<chrono::NaiveDateTime>,
+    pub created_after: Option<chrono::NaiveDateTime>,
+    pub updated_before: Option<chrono::NaiveDateTime>,
+    pub updated_after: Option<chrono::NaiveDateTime>,
+    /// tasks tagged with any of these tags
+    pub tags: Option<Vec<ulid::Ulid>>,
+    /// tasks tagged with all of these tags
+    pub all_tags: Option<Vec<ulid::Ulid>>,
+    pub project: Option<ProjectFilter>,
+    pub workspace: Option<WorkspaceFilter>,
+    pub assignee: Option<ulid::Ulid>,
+    /// structured query given as `q`
+    pub expr: Option<FilterExpr>,
+    pub archived: ArchivedFilter,
+}
+impl TaskFilter {
+    fn to_prepared_query(&self) -> Vec<String> {
+        let mut query = Vec::new();
+
+        if self.phrase.is_some() {
+            query.push("(`todos`.`title` LIKE ? OR `todos`.`description` LIKE ?)".to_string());
+        }
+        if self.search.is_some() {
+            query.push(format!("{} > 0", Self::MATCH_QUERY));
+        }
+        if let Some(state) = &self.state {
+            query.push(Self::in_query("`todos`.`state`", state.len()));
+        }
+        if let Some(priority) = &self.priority {
+            query.push(Self::priority_query(priority));
+        }
+        if self.due_before.is_some() {
+            query.push("`todos`.`due_date` < ?".to_string());
+        }
+        if self.due_after.is_some() {
+            query.push("`todos`.`due_date` > ?".to_string());
+        }
+        match self.overdue {
+            Some(true) => query.push(Self::OVERDUE_QUERY.to_string()),
+            Some(false) => query.push(
+                "(`todos`.`due_date` IS NULL OR `todos`.`due_date` >= NOW() OR `todos`.`state` = 'done')"
+                    .to_string(),
+            ),
+            None => (),
+        }
+        if self.created_before.is_some() {
+            query.push("`todos`.`created_at` < ?".to_string());
+        }
+        if self.created_after.is_some() {
+            query.push("`todos`.`created_at` > ?".to_string());
+        }
+        if self.updated_before.is_some() {
+            query.push("`todos`.`updated_at` < ?".to_string());
+        }
+        if self.updated_after.is_some() {
+            query.push("`todos`.`updated_at` > ?".to_string());
+        }
+        if let Some(tags) = &self.tags {
+            query.push(format!(
+                r#"EXISTS (
+                    SELECT 1 FROM `todo_taggings`
+                        WHERE `todo_taggings`.`todo_id` = `todos`.`id`
+                        AND {})"#,
+                Self::in_query("`todo_taggings`.`tag_id`", tags.len())
+            ));
+        }
+        if let Some(all_tags) = &self.all_tags {
+            if !all_tags.is_empty() {
+                query.push(format!(
+                    r#"`todos`.`id` IN (
+                        SELECT `todo_taggings`.`todo_id` FROM `todo_taggings`
+                            WHERE {}
+                            GROUP BY `todo_taggings`.`todo_id`
+                            HAVING COUNT(DISTINCT `todo_taggings`.`tag_id`) = ?)"#,
+                    Self::in_query("`todo_taggings`.`tag_id`", all_tags.len())
+                ));
+            }
+        }
+        match self.project {
+            Some(ProjectFilter::Inbox) => query.push("`todos`.`project_id` IS NULL".to_string()),
+            Some(ProjectFilter::Project(_)) => query.push("`todos`.`project_id` = ?".to_string()),
+            None => (),
+        }
+        match self.workspace {
+            Some(WorkspaceFilter::Personal) => {
+                query.push("`todos`.`workspace_id` IS NULL".to_string())
+            }
+            Some(WorkspaceFilter::Workspace(_)) => {
+                query.push("`todos`.`workspace_id` = ?".to_string())
+            }
+            None => (),
+        }
+        if self.assignee.is_some() {
+            query.push("`todos`.`assignee_id` = ?".to_string());
+        }
+        if let Some(expr) = &self.expr {
+            query.push(expr.to_prepared_query());
+        }
+        match self.archived {
+            ArchivedFilter::Exclude => query.push("`todos`.`archived_at` IS NULL".to_string()),
+            ArchivedFilter::Include => (),
+            ArchivedFilter::Only => query.push("`todos`.`archived_at` IS NOT NULL".to_string()),
+        }
+
+        query
+    }
+
+    const MATCH_QUERY: &'static str =
+        "MATCH (`todos`.`title`, `todos`.`description`) AGAINST (? IN BOOLEAN MODE)";
+
+    fn in_query(column: &str, len: usize) -> String {
+        if len == 0 {
+            return "FALSE".to_string();
+        }
+        format!("{} IN ({})", column, vec!["?"; len].join(", "))
+    }
+
+    const OVERDUE_QUERY: &'static str =
+        "(`todos`.`due_date` < NOW() AND `todos`.`state` <> 'done')";
+
+    fn priority_query(priority: &[Option<types::TaskPriority>]) -> String {
+        let values = priority.iter().filter(|p| p.is_some()).count();
+        let has_none = priority.iter().any(|p| p.is_none());
+        match (values, has_none) {
+            (0, true) => "`todos`.`priority` IS NULL".to_string(),
+            (_, true) => format!(
+                "({} OR `todos`.`priority` IS NULL)",
+                Self::in_query("`todos`.`priority`", values)
+            ),
+            (_, false) => Self::in_query("`todos`.`priority`", values),
+        }
+    }
+
+    /// escapes `LIKE` wildcards so `s` matches literally
+    fn escape_like(s: &str) -> String {
+        s.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    }
+
+    fn add_arguments(&self, args: &mut MySqlArguments) {
+        if let Some(phrase) = &self.phrase {
+            let phrase = format!("%{}%", Self::escape_like(phrase));
+            args.add(phrase.clone());
+            args.add(phrase);
+        }
+        if let Some(search) = &self.search {
+            args.add(search.to_boolean_query());
+        }
+        if let Some(state) = &self.state {
+            for s in state {
+                args.add(*s);
+            }
+        }
+        if let Some(priority) = &self.priority {
+            for p in priority.iter().flatten() {
+                args.add(*p);
+            }
+        }
+        for date in [
+            self.due_before,
+            self.due_after,
+            self.created_before,
+            self.created_after,
+            self.updated_before,
+            self.updated_after,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            args.add(date);
+        }
+        if let Some(tags) = &self.tags {
+            for tag in tags {
+                args.add(ulid_to_binary(*tag).to_vec());
+            }
+        }
+        if let Some(all_tags) = &self.all_tags {
+            if !all_tags.is_empty() {
+                for tag in all_tags {
+                    args.add(ulid_to_binary(*tag).to_vec());
+                }
+                let distinct_len = all_tags.iter().collect::<HashSet<_>>().len();
+                args.add(distinct_len as i64);
+            }
+        }
+        if let Some(ProjectFilter::Project(project)) = self.project {
+            args.add(ulid_to_binary(project).to_vec());
+        }
+        if let Some(WorkspaceFilter::Workspace(workspace)) = self.workspace {
+            args.add(ulid_to_binary(workspace).to_vec());
+        }
+        if let Some(assignee) = self.assignee {
+            args.add(ulid_to_binary(assignee).to_vec());
+        }
+        if let Some(expr) = &self.expr {
+            expr.add_arguments(args);
+        }
+    }
+}
+
+/// tasks of the user along with the ones shared with them
 pub async fn get_tasks(
     conn: impl Acquire<'_, Database = MySql>,
-    author_id: ulid::Ulid,
-    phrase: Option<String>,
+    user_id: ulid::Ulid,
+    filter: &TaskFilter,
     limit: Option<Limit>,
     sorted_by: Option<SortedBy>,
-
-    state_filter: Option<Vec<types::TaskState>>,
+    count_total: bool,
 ) -> anyhow::Result<VecWithTotal<types::Todo>> {
     let mut conn = conn.acquire().await?;
 
-    let mut query = "SELECT SQL_CALC_FOUND_ROWS * FROM `todos` WHERE `author_id` = ?".to_string();
-    if phrase.is_some() {
-        query.push_str(" AND (`title` LIKE ? OR `description` LIKE ?)");
-    }
-    if let Some(state_filter) = state_filter {
-        query.push_str(" AND `state` IN (");
-        query.push_str(
-            &state_filter
-                .iter()
-                .map(|s| format!("'{}'", s.to_string()))
-                .collect::<Vec<_>>()
-                .join(", "),
-        );
-        query.push(')');
+    let sorted_by = sorted_by.unwrap_or_default();
+    let cursor = match &limit {
+        Some(Limit::Cursor(_, cursor)) => Some(cursor),
+        _ => None,
+    };
+    let direction = cursor.map(|c| c.direction());
+    let is_reversed = direction == Some(Direction::Prev);
+
+    let bin_id = ulid_to_binary(user_id);
+
+    let mut args = MySqlArguments::default();
+
+    // filtered rows are wrapped in a derived table so that sorting and cursors can refer to
+    // `score` like any other column
+    let mut query = "SELECT * FROM (SELECT `todos`.*".to_string();
+    if let Some(search) = &filter.search {
+        query.push_str(&format!(", {} AS `score`", TaskFilter::MATCH_QUERY));
+        args.add(search.to_boolean_query());
     }
     query.push_str(&format!(
-        " {}",
-        SortedBy::CreatedAt(Order::Desc).to_query().as_str()
+        " FROM `todos` WHERE {} AND `todos`.`deleted_at` IS NULL",
+        super::shares::VISIBLE_TASK_QUERY
     ));
-    query.push_str(&format!(
-        " {}",
-        limit.map(|l| l.to_prepared_query()).unwrap_or_default()
-    ));
-    query.push(';');
+    for _ in 0..super::shares::VISIBLE_TASK_BINDS {
+        args.add(bin_id.as_slice());
+    }
+
+    for q in filter.to_prepared_query() {
+        query.push_str(&format!(" AND {}", q));
+    }
+    filter.add_arguments(&mut args);
+    query.push_str(") AS `todos`");
 
-    let bin_id = ulid_to_binary(author_id);
+    if let Some(cursor) = cursor {
+        query.push_str(&format!(" WHERE {}", cursor.to_prepared_query(&sorted_by)));
+        cursor.add_arguments(&mut args)?;
+    }
+
+    if is_reversed {
+        query.push_str(&format!(" {}", sorted_by.to_reversed_query()));
+    } else {
+        query.push_str(&format!(" {}", sorted_by.to_query()));
+    }
 
-    let building_query = {
-        let mut building_query =
-            sqlx::query_as::<_, types::Todo>(query.as_str()).bind(bin_id.as_slice());
+    if let Some(limit) = &limit {
+        query.push_str(&format!(" {}", limit.to_prepared_query()));
+        limit.add_arguments(&mut args);
+    }
+    query.push(';');
+
+    let mut rows = sqlx::query_as_with::<_, types::Todo, _>(query.as_str(), args)
+        .fetch_all(&mut *conn)
+        .await?;
 
-        if let Some(phrase) = phrase {
-            let phrase = format!("%{}%", phrase);
-            building_query = building_query.bind(phrase.clone()).bind(phrase);
+    let (mut next_cursor, mut prev_cursor) = (None, None);
+    if let Some(Limit::Limit(limit) | Limit::Cursor(limit, _)) = &limit {
+        let has_more = rows.len() > *limit;
+        rows.truncate(*limit);
+        if is_reversed {
+            rows.reverse();
         }
 
-        match limit {
-            Some(Limit::LimitOffset(limit, offset)) => {
-                building_query = building_query.bind(limit as i64).bind(offset as i64)
+        let (has_next, has_prev) = match direction {
+            None => (has_more, false),
+            Some(Direction::Next) => (has_more, true),
+            Some(Direction::Prev) => (true, has_more),
+        };
+        if has_next {
+            if let Some(last) = rows.last() {
+                next_cursor = Some(Cursor::new(Direction::Next, &sorted_by, last)?.encode()?);
+            }
+        }
+        if has_prev {
+            if let Some(first) = rows.first() {
+                prev_cursor = Some(Cursor::new(Direction::Prev, &sorted_by, first)?.encode()?);
             }
-            Some(Limit::Limit(limit)) => building_query = building_query.bind(limit as i64),
-            None => (),
         }
-        building_query
+    }
+
+    let total = if count_total {
+        Some(count_tasks(&mut *conn, user_id, filter).await?)
+    } else {
+        None
     };
 
-    let rows = building_query.fetch_all(&mut *conn).await?;
+    Ok(VecWithTotal {
+        total,
+        items: rows,
+        tag_counts: None,
+        next_cursor,
+        prev_cursor,
+    })
+}
+
+pub async fn count_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    filter: &TaskFilter,
+) -> anyhow::Result<usize> {
+    let mut conn = conn.acquire().await?;
 
-    let total = sqlx::query_as::<_, (i64,)>("SELECT FOUND_ROWS()")
+    let mut query = format!(
+        "SELECT COUNT(*) FROM `todos` WHERE {} AND `todos`.`deleted_at` IS NULL",
+        super::shares::VISIBLE_TASK_QUERY
+    );
+    for q in filter.to_prepared_query() {
+        query.push_str(&format!(" AND {}", q));
+    }
+    query.push(';');
+
+    let bin_id = ulid_to_binary(user_id);
+
+    let mut args = MySqlArguments::default();
+    for _ in 0..super::shares::VISIBLE_TASK_BINDS {
+        args.add(bin_id.as_slice());
+    }
+    filter.add_arguments(&mut args);
+
+    let count = sqlx::query_with(query.as_str(), args)
         .fetch_one(&mut *conn)
         .await?
-        .0 as usize;
+        .get::<i64, _>(0);
+
+    Ok(count as usize)
+}
 
-    Ok(VecWithTotal { total, items: rows })
+#[derive(Debug, Clone, FromRow)]
+struct TagCountRow {
+    id: Vec<u8>,
+    name: String,
+    count: i64,
+}
+
+/// condition on `tags` and `todos` matching the personal tags of the user and the tags of the
+/// workspace of the task when they are a member, like the tags they may put on the task,
+/// binding the user id twice
+const OWN_TAG_QUERY: &str = r#"((`tags`.`workspace_id` IS NULL AND `tags`.`author_id` = ?)
+    OR (`tags`.`workspace_id` = `todos`.`workspace_id` AND EXISTS (
+        SELECT 1 FROM `workspace_members`
+            WHERE `workspace_members`.`workspace_id` = `tags`.`workspace_id`
+            AND `workspace_members`.`user_id` = ?)))"#;
+
+/// counts the tasks matching `filter` for each tag, ignoring pagination
+pub async fn get_tag_counts(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    filter: &TaskFilter,
+) -> anyhow::Result<Vec<types::TagCount>> {
+    let mut conn = conn.acquire().await?;
+
+    let mut query = format!(
+        r#"
+        SELECT `tags`.`id`, `tags`.`name`, COUNT(*) AS `count` FROM `todos`
+            INNER JOIN `todo_taggings` ON `todo_taggings`.`todo_id` = `todos`.`id`
+            INNER JOIN `tags` ON `tags`.`id` = `todo_taggings`.`tag_id`
+            WHERE {} AND `todos`.`deleted_at` IS NULL
+            AND {}"#,
+        super::shares::VISIBLE_TASK_QUERY,
+        OWN_TAG_QUERY
+    );
+    for q in filter.to_prepared_query() {
+        query.push_str(&format!(" AND {}", q));
+    }
+    query.push_str(" GROUP BY `tags`.`id`, `tags`.`name` ORDER BY `tags`.`name` ASC;");
+
+    let bin_id = ulid_to_binary(user_id);
+
+    let mut args = MySqlArguments::default();
+    for _ in 0..super::shares::VISIBLE_TASK_BINDS + 2 {
+        args.add(bin_id.as_slice());
+    }
+    filter.add_arguments(&mut args);
+
+    let rows = sqlx::query_as_with::<_, TagCountRow, _>(query.as_str(), args)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(types::TagCount {
+                tag_id: binary_to_ulid(row.id.as_slice())?.to_string(),
+                name: row.name,
+                count: row.count as usize,
+            })
+        })
+        .collect()
 }
 
 pub async fn get_task(
@@ -177,7 +639,7 @@ pub async fn get_task(
 ) -> anyhow::Result<Option<types::Todo>> {
     let mut conn = conn.acquire().await?;
 
-    let query = "SELECT * FROM `todos` WHERE `id` = ?;";
+    let query = "SELECT * FROM `todos` WHERE `id` = ? AND `deleted_at` IS NULL;";
 
     let bin_task_id = ulid_to_binary(task_id);
 
@@ -195,7 +657,7 @@ pub async fn get_task_with_lock(
 ) -> anyhow::Result<Option<types::Todo>> {
     let mut conn = conn.acquire().await?;
 
-    let query = "SELECT * FROM `todos` WHERE `id` = ? FOR UPDATE;";
+    let query = "SELECT * FROM `todos` WHERE `id` = ? AND `deleted_at` IS NULL FOR UPDATE;";
 
     let bin_task_id = ulid_to_binary(task_id);
 
@@ -207,6 +669,26 @@ pub async fn get_task_with_lock(
     Ok(row)
 }
 
+/// whether the id is already taken, including by a task in the trash
+pub async fn task_exists(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<bool> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT COUNT(*) FROM `todos` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    let count = sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(count > 0)
+}
+
 pub async fn insert_task(
     conn: impl Acquire<'_, Database = MySql>,
     task: types::TodoReq,
@@ -215,19 +697,24 @@ pub async fn insert_task(
 
     let query = r#"
         INSERT INTO `todos`
-            (`id`, `author_id`, `title`, `description`, `state`, `priority`, `due_date`)
-            VALUES (?, ?, ?, ?, ?, ?, ?);"#;
+            (`id`, `author_id`, `assignee_id`, `parent_id`, `project_id`, `workspace_id`, `title`, `description`, `state`, `priority`, `due_date`, `recurrence`)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);"#;
 
     let priority_str: Option<String> = task.priority.map(|p| p.to_string());
 
     sqlx::query(query)
         .bind(task.id)
         .bind(task.author_id)
+        .bind(task.assignee_id)
+        .bind(task.parent_id)
+        .bind(task.project_id)
+        .bind(task.workspace_id)
         .bind(task.title)
         .bind(task.description)
         .bind(task.state)
         .bind(priority_str)
         .bind(task.due_date)
+        .bind(task.recurrence)
         .execute(&mut *conn)
         .await?;
 
@@ -241,6 +728,8 @@ pub struct UpdateTask {
     pub state: Update<types::TaskState>,
     pub priority: Update<Option<types::TaskPriority>>,
     pub due_date: Update<Option<chrono::NaiveDateTime>>,
+    pub recurrence: Update<Option<types::Recurrence>>,
+    pub assignee_id: Update<Option<Vec<u8>>>,
 }
 impl UpdateTask {
     fn to_prepared_query(&self) -> String {
@@ -261,6 +750,12 @@ impl UpdateTask {
         if let Some(q) = self.due_date.to_prepared_query("due_date") {
             query.push(q);
         }
+        if let Some(q) = self.recurrence.to_prepared_query("recurrence") {
+            query.push(q);
+        }
+        if let Some(q) = self.assignee_id.to_prepared_query("assignee_id") {
+            query.push(q);
+        }
 
         query.join(", ")
     }
@@ -274,6 +769,8 @@ impl UpdateTask {
         query = self.state.bind_query(query);
         query = self.priority.bind_query(query);
         query = self.due_date.bind_query(query);
+        query = self.recurrence.bind_query(query);
+        query = self.assignee_id.bind_query(query);
 
         query
     }
@@ -284,6 +781,8 @@ impl UpdateTask {
             && self.state.is_nop()
             && self.priority.is_nop()
             && self.due_date.is_nop()
+            && self.recurrence.is_nop()
+            && self.assignee_id.is_nop()
     }
 }
 
@@ -299,7 +798,7 @@ pub async fn update_task(
     }
 
     let query = format!(
-        "UPDATE `todos` SET {} WHERE `id` = ?;",
+        "UPDATE `todos` SET {}, `version` = `version` + 1 WHERE `id` = ?;",
         update.to_prepared_query()
     );
 
@@ -314,7 +813,179 @@ pub async fn update_task(
     Ok(())
 }
 
-pub async fn delete_task(
+/// archives the task, or takes it out of the archive with `false`
+pub async fn set_archived(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    archived: bool,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = if archived {
+        "UPDATE `todos` SET `archived_at` = NOW(), `version` = `version` + 1 WHERE `id` = ? AND `archived_at` IS NULL;"
+    } else {
+        "UPDATE `todos` SET `archived_at` = NULL, `version` = `version` + 1 WHERE `id` = ? AND `archived_at` IS NOT NULL;"
+    };
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// archives the done tasks left untouched for longer than the auto-archive rule of their author,
+/// returning how many were archived
+pub async fn auto_archive_tasks(conn: impl Acquire<'_, Database = MySql>) -> anyhow::Result<u64> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        UPDATE `todos`
+            INNER JOIN `users` ON `users`.`id` = `todos`.`author_id`
+            SET `todos`.`archived_at` = NOW(), `todos`.`version` = `todos`.`version` + 1
+            WHERE `users`.`auto_archive_days` IS NOT NULL
+            AND `todos`.`state` = 'done'
+            AND `todos`.`archived_at` IS NULL
+            AND `todos`.`deleted_at` IS NULL
+            AND `todos`.`updated_at` < NOW() - INTERVAL `users`.`auto_archive_days` DAY;"#;
+
+    let result = sqlx::query(query).execute(&mut *conn).await?;
+
+    Ok(result.rows_affected())
+}
+
+/// moves the tasks to the trash at the same time, so that they are restored together
+pub async fn trash_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    ids: &[ulid::Ulid],
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = format!(
+        "UPDATE `todos` SET `deleted_at` = NOW(), `version` = `version` + 1 WHERE `id` IN ({}) AND `deleted_at` IS NULL;",
+        vec!["?"; ids.len()].join(", ")
+    );
+
+    let bin_ids = ids.iter().map(|id| ulid_to_binary(*id)).collect::<Vec<_>>();
+
+    let mut building_query = sqlx::query(query.as_str());
+    for bin_id in bin_ids.iter() {
+        building_query = building_query.bind(bin_id.as_slice());
+    }
+    building_query.execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// trashed tasks the user owns, most recently deleted first
+pub async fn get_trashed_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Todo>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = format!(
+        r#"
+        SELECT * FROM `todos`
+            WHERE {} AND `deleted_at` IS NOT NULL
+            ORDER BY `deleted_at` DESC, `id` DESC;"#,
+        super::shares::OWNED_TASK_QUERY
+    );
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let mut building_query = sqlx::query_as::<_, types::Todo>(query.as_str());
+    for _ in 0..super::shares::OWNED_TASK_BINDS {
+        building_query = building_query.bind(bin_user_id.as_slice());
+    }
+    let rows = building_query.fetch_all(&mut *conn).await?;
+
+    Ok(rows)
+}
+
+pub async fn get_trashed_task_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Todo>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `todos` WHERE `id` = ? AND `deleted_at` IS NOT NULL FOR UPDATE;";
+
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let row = sqlx::query_as::<_, types::Todo>(query)
+        .bind(bin_task_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+/// takes the trashed task out of the trash with the subtasks trashed along with it,
+/// returning them, the task first
+///
+/// the task moves to the top level when its parent is still in the trash
+pub async fn restore_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    task: &types::Todo,
+) -> anyhow::Result<Vec<types::Todo>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        WITH RECURSIVE `subtasks` AS (
+            SELECT `todos`.*, 0 AS `depth` FROM `todos` WHERE `id` = ?
+            UNION ALL
+            SELECT `todos`.*, `subtasks`.`depth` + 1 FROM `todos`
+                INNER JOIN `subtasks` ON `todos`.`parent_id` = `subtasks`.`id`
+                WHERE `todos`.`deleted_at` = ?
+        )
+        SELECT * FROM `subtasks` ORDER BY `depth` ASC, `created_at` ASC, `id` ASC;"#;
+
+    let restored = sqlx::query_as::<_, types::Todo>(query)
+        .bind(task.id.as_slice())
+        .bind(task.deleted_at)
+        .fetch_all(&mut *conn)
+        .await?;
+    if restored.is_empty() {
+        return Ok(restored);
+    }
+
+    let query = format!(
+        "UPDATE `todos` SET `deleted_at` = NULL, `version` = `version` + 1 WHERE `id` IN ({});",
+        vec!["?"; restored.len()].join(", ")
+    );
+
+    let mut building_query = sqlx::query(query.as_str());
+    for restored in restored.iter() {
+        building_query = building_query.bind(restored.id.as_slice());
+    }
+    building_query.execute(&mut *conn).await?;
+
+    let query = r#"
+        UPDATE `todos` SET `parent_id` = NULL, `version` = `version` + 1
+            WHERE `id` = ? AND `parent_id` IN (
+                SELECT `id` FROM (
+                    SELECT `id` FROM `todos` WHERE `deleted_at` IS NOT NULL
+                ) AS `trashed`
+            );"#;
+
+    sqlx::query(query)
+        .bind(task.id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(restored)
+}
+
+/// permanently deletes the task with its subtasks, tags and everything attached to it
+pub async fn purge_task(
     conn: impl Acquire<'_, Database = MySql>,
     id: ulid::Ulid,
 ) -> anyhow::Result<()> {
@@ -331,3 +1002,321 @@ pub async fn delete_task(
 
     Ok(())
 }
+
+/// permanently deletes the trashed tasks the user owns
+pub async fn purge_trash(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = format!(
+        "DELETE FROM `todos` WHERE {} AND `deleted_at` IS NOT NULL;",
+        super::shares::OWNED_TASK_QUERY
+    );
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let mut building_query = sqlx::query(query.as_str());
+    for _ in 0..super::shares::OWNED_TASK_BINDS {
+        building_query = building_query.bind(bin_user_id.as_slice());
+    }
+    building_query.execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// permanently deletes the tasks trashed more than `retention_secs` seconds ago,
+/// returning how many were deleted
+pub async fn purge_expired_trash(
+    conn: impl Acquire<'_, Database = MySql>,
+    retention_secs: i64,
+) -> anyhow::Result<u64> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `todos` WHERE `deleted_at` < NOW() - INTERVAL ? SECOND;";
+
+    let result = sqlx::query(query)
+        .bind(retention_secs)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// all the descendants of the task, parents before their children
+pub async fn get_subtasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Todo>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        WITH RECURSIVE `subtasks` AS (
+            SELECT `todos`.*, 1 AS `depth` FROM `todos`
+                WHERE `parent_id` = ? AND `deleted_at` IS NULL
+            UNION ALL
+            SELECT `todos`.*, `subtasks`.`depth` + 1 FROM `todos`
+                INNER JOIN `subtasks` ON `todos`.`parent_id` = `subtasks`.`id`
+                WHERE `todos`.`deleted_at` IS NULL
+        )
+        SELECT * FROM `subtasks` ORDER BY `depth` ASC, `created_at` ASC, `id` ASC;"#;
+
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let rows = sqlx::query_as::<_, types::Todo>(query)
+        .bind(bin_task_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+/// the descendants of the task the user can see, parents before their children, leaving out
+/// the subtasks under the ones they cannot see
+pub async fn get_visible_subtasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Todo>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = format!(
+        r#"
+        WITH RECURSIVE `subtasks` AS (
+            SELECT `todos`.*, 1 AS `depth` FROM `todos`
+                WHERE `parent_id` = ? AND `deleted_at` IS NULL AND {visible}
+            UNION ALL
+            SELECT `todos`.*, `subtasks`.`depth` + 1 FROM `todos`
+                INNER JOIN `subtasks` ON `todos`.`parent_id` = `subtasks`.`id`
+                WHERE `todos`.`deleted_at` IS NULL AND {visible}
+        )
+        SELECT * FROM `subtasks` ORDER BY `depth` ASC, `created_at` ASC, `id` ASC;"#,
+        visible = super::shares::VISIBLE_TASK_QUERY
+    );
+
+    let bin_task_id = ulid_to_binary(task_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let mut building_query =
+        sqlx::query_as::<_, types::Todo>(query.as_str()).bind(bin_task_id.as_slice());
+    for _ in 0..2 * super::shares::VISIBLE_TASK_BINDS {
+        building_query = building_query.bind(bin_user_id.as_slice());
+    }
+    let rows = building_query.fetch_all(&mut *conn).await?;
+
+    Ok(rows)
+}
+
+/// ids of the task and all of its ancestors, the task first
+pub async fn get_ancestor_ids(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_id: ulid::Ulid,
+) -> anyhow::Result<Vec<ulid::Ulid>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        WITH RECURSIVE `ancestors` AS (
+            SELECT `id`, `parent_id`, 0 AS `depth` FROM `todos` WHERE `id` = ?
+            UNION ALL
+            SELECT `todos`.`id`, `todos`.`parent_id`, `ancestors`.`depth` + 1 FROM `todos`
+                INNER JOIN `ancestors` ON `todos`.`id` = `ancestors`.`parent_id`
+        )
+        SELECT `id` FROM `ancestors` ORDER BY `depth` ASC;"#;
+
+    let bin_task_id = ulid_to_binary(task_id);
+
+    let rows = sqlx::query(query)
+        .bind(bin_task_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    rows.iter()
+        .map(|row| binary_to_ulid(row.get::<Vec<u8>, _>(0).as_slice()))
+        .collect()
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct ChildCountRow {
+    parent_id: Vec<u8>,
+    total: i64,
+    done: i64,
+}
+
+/// direct subtasks of each task, counting only the ones the user can see
+pub async fn get_child_counts(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+    task_ids: &[ulid::Ulid],
+) -> anyhow::Result<HashMap<ulid::Ulid, types::ChildCount>> {
+    let mut conn = conn.acquire().await?;
+
+    if task_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let query = format!(
+        r#"
+        SELECT
+            `parent_id`,
+            COUNT(*) AS `total`,
+            CAST(SUM(`state` = 'done') AS SIGNED) AS `done`
+            FROM `todos`
+            WHERE `parent_id` IN ({}) AND `deleted_at` IS NULL AND {}
+            GROUP BY `parent_id`;"#,
+        vec!["?"; task_ids.len()].join(", "),
+        super::shares::VISIBLE_TASK_QUERY
+    );
+
+    let bin_task_ids = task_ids
+        .iter()
+        .map(|id| ulid_to_binary(*id))
+        .collect::<Vec<_>>();
+
+    let mut building_query = sqlx::query_as::<_, ChildCountRow>(query.as_str());
+    for bin_task_id in bin_task_ids.iter() {
+        building_query = building_query.bind(bin_task_id.as_slice());
+    }
+    let bin_user_id = ulid_to_binary(user_id);
+    for _ in 0..super::shares::VISIBLE_TASK_BINDS {
+        building_query = building_query.bind(bin_user_id.as_slice());
+    }
+
+    let rows = building_query.fetch_all(&mut *conn).await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let parent_id = binary_to_ulid(row.parent_id.as_slice())?;
+            let count = types::ChildCount {
+                total: row.total as usize,
+                done: row.done as usize,
+            };
+            Ok((parent_id, count))
+        })
+        .collect()
+}
+
+/// moves the task under `parent_id`, or to the top level with `None`
+pub async fn set_parent(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    parent_id: Option<ulid::Ulid>,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "UPDATE `todos` SET `parent_id` = ?, `version` = `version` + 1 WHERE `id` = ?;";
+
+    let bin_parent_id = parent_id.map(|id| ulid_to_binary(id).to_vec());
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_parent_id)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// moves the tasks to the project, or to the inbox with `None`
+pub async fn set_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    ids: &[ulid::Ulid],
+    project_id: Option<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let query = format!(
+        "UPDATE `todos` SET `project_id` = ?, `version` = `version` + 1 WHERE `id` IN ({});",
+        vec!["?"; ids.len()].join(", ")
+    );
+
+    let bin_ids = ids.iter().map(|id| ulid_to_binary(*id)).collect::<Vec<_>>();
+
+    let mut building_query = sqlx::query(query.as_str()).bind(project_id);
+    for bin_id in bin_ids.iter() {
+        building_query = building_query.bind(bin_id.as_slice());
+    }
+    building_query.execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// moves the direct children of the task under `parent_id`, leaving the trashed ones so that
+/// they are restored under the task
+pub async fn reparent_children(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    parent_id: Option<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        UPDATE `todos` SET `parent_id` = ?, `version` = `version` + 1
+            WHERE `parent_id` = ? AND `deleted_at` IS NULL;"#;
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(parent_id)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// creates the occurrence following the recurring `task` with the same content and tags,
+/// returning it, or `None` when the task does not recur any more
+///
+/// tasks without a due date recur from `now`
+pub async fn insert_next_occurrence(
+    conn: impl Acquire<'_, Database = MySql>,
+    task: &types::Todo,
+    now: chrono::NaiveDateTime,
+) -> anyhow::Result<Option<types::TodoReq>> {
+    let mut conn = conn.acquire().await?;
+
+    let Some(recurrence) = &task.recurrence else {
+        return Ok(None);
+    };
+    let Some((due_date, recurrence)) = recurrence.next(task.due_date.unwrap_or(now)) else {
+        return Ok(None);
+    };
+
+    let task_ulid = ulid::Ulid::new();
+    let next = types::TodoReq {
+        id: ulid_to_binary(task_ulid).to_vec(),
+        author_id: task.author_id.clone(),
+        assignee_id: task.assignee_id.clone(),
+        parent_id: task.parent_id.clone(),
+        project_id: task.project_id.clone(),
+        workspace_id: task.workspace_id.clone(),
+        title: task.title.clone(),
+        description: task.description.clone(),
+        state: types::TaskState::Todo,
+        priority: task.priority,
+        due_date: Some(due_date),
+        recurrence: Some(recurrence),
+    };
+
+    insert_task(&mut *conn, next.clone()).await?;
+
+    let query = r#"
+        INSERT INTO `todo_taggings` (`todo_id`, `tag_id`)
+            SELECT ?, `tag_id` FROM `todo_taggings` WHERE `todo_id` = ?;"#;
+
+    let bin_task_ulid = ulid_to_binary(task_ulid);
+
+    sqlx::query(query)
+        .bind(bin_task_ulid.as_slice())
+        .bind(task.id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Some(next))
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::types;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SearchTerm {
+    text: String,
+    is_phrase: bool,
+    is_excluded: bool,
+}
+
+/// full-text search input such as `release "weekly report" -draft`
+///
+/// every word and quoted phrase is required, and the ones prefixed with `-` are excluded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    terms: Vec<SearchTerm>,
+}
+impl SearchQuery {
+    /// characters with a meaning in `IN BOOLEAN MODE`, dropped from user input
+    const OPERATOR_CHARS: &'static [char] = &['+', '-', '<', '>', '(', ')', '~', '*', '"', '@'];
+
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let mut terms = Vec::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+
+            let is_excluded = c == '-';
+            if is_excluded {
+                chars.next();
+            }
+
+            let (raw, is_phrase) = if chars.peek() == Some(&'"') {
+                chars.next();
+                let raw = chars.by_ref().take_while(|c| *c != '"').collect::<String>();
+                (raw, true)
+            } else {
+                let mut raw = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    raw.push(c);
+                }
+                (raw, false)
+            };
+
+            let text = raw
+                .split(|c: char| c.is_whitespace() || Self::OPERATOR_CHARS.contains(&c))
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if text.is_empty() {
+                continue;
+            }
+
+            terms.push(SearchTerm {
+                is_phrase: is_phrase || text.contains(' '),
+                text,
+                is_excluded,
+            });
+        }
+
+        if terms.iter().all(|t| t.is_excluded) {
+            anyhow::bail!("Search needs at least one term that is not excluded");
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// the expression for `MATCH ... AGAINST (? IN BOOLEAN MODE)`
+    pub fn to_boolean_query(&self) -> String {
+        self.terms
+            .iter()
+            .map(|term| {
+                let operator = if term.is_excluded { "-" } else { "+" };
+                if term.is_phrase {
+                    format!("{}\"{}\"", operator, term.text)
+                } else if term.is_excluded {
+                    format!("{}{}", operator, term.text)
+                } else {
+                    format!("{}{}*", operator, term.text)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    const SNIPPET_LEN: usize = 160;
+
+    pub fn to_match(&self, task: &types::Todo) -> SearchMatch {
+        let words = self
+            .terms
+            .iter()
+            .filter(|t| !t.is_excluded)
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>();
+
+        SearchMatch {
+            score: task.score.unwrap_or_default(),
+            title: Highlight::new(&task.title, &words, None),
+            description: Highlight::new(&task.description, &words, Some(Self::SNIPPET_LEN)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub score: f64,
+    pub title: Highlight,
+    pub description: Highlight,
+}
+
+/// `text` with the `[start, end)` char ranges that matched the search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Highlight {
+    pub text: String,
+    pub ranges: Vec<(usize, usize)>,
+}
+impl Highlight {
+    const ELLIPSIS: char = '…';
+
+    /// finds `words` case-insensitively, cutting `text` down to about `max_len` chars around the
+    /// first match
+    fn new(text: &str, words: &[&str], max_len: Option<usize>) -> Self {
+        let chars = text.chars().collect::<Vec<_>>();
+        let lower = chars.iter().map(|c| Self::fold(*c)).collect::<Vec<_>>();
+
+        let mut ranges = Vec::new();
+        for word in words {
+            let word = word.chars().map(Self::fold).collect::<Vec<_>>();
+            if word.is_empty() || word.len() > lower.len() {
+                continue;
+            }
+            let mut start = 0;
+            while start + word.len() <= lower.len() {
+                if lower[start..start + word.len()] == word[..] {
+                    ranges.push((start, start + word.len()));
+                    start += word.len();
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        ranges.sort_unstable();
+        let ranges = ranges
+            .into_iter()
+            .fold(Vec::new(), |mut merged, (start, end)| {
+                match merged.last_mut() {
+                    Some((_, last_end)) if start <= *last_end => *last_end = end.max(*last_end),
+                    _ => merged.push((start, end)),
+                }
+                merged
+            });
+
+        let (from, to) = match max_len {
+            Some(max_len) if chars.len() > max_len => {
+                let first = ranges.first().map(|(start, _)| *start).unwrap_or(0);
+                let from = first.saturating_sub(max_len / 4);
+                let to = (from + max_len).min(chars.len());
+                (to.saturating_sub(max_len), to)
+            }
+            _ => (0, chars.len()),
+        };
+
+        let mut snippet = String::new();
+        let mut offset = 0;
+        if from > 0 {
+            snippet.push(Self::ELLIPSIS);
+            offset = 1;
+        }
+        snippet.extend(&chars[from..to]);
+        if to < chars.len() {
+            snippet.push(Self::ELLIPSIS);
+        }
+
+        let ranges = ranges
+            .into_iter()
+            .filter(|(start, end)| *start >= from && *end <= to)
+            .map(|(start, end)| (start - from + offset, end - from + offset))
+            .collect();
+
+        Self {
+            text: snippet,
+            ranges,
+        }
+    }
+
+    fn fold(c: char) -> char {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boolean_query(s: &str) -> String {
+        SearchQuery::parse(s).unwrap().to_boolean_query()
+    }
+
+    #[test]
+    fn words_phrases_and_exclusions() {
+        assert_eq!(
+            boolean_query(r#"release "weekly report" -draft"#),
+            r#"+release* +"weekly report" -draft"#
+        );
+        assert_eq!(
+            boolean_query(r#"-"old notes"  plan"#),
+            r#"-"old notes" +plan*"#
+        );
+    }
+
+    #[test]
+    fn drops_operators() {
+        assert_eq!(boolean_query("+a* (b) ~c@d"), r#"+a* +b* +"c d""#);
+        assert_eq!(boolean_query(r#"x -"" "<>""#), "+x*");
+    }
+
+    #[test]
+    fn unterminated_quote_runs_to_the_end() {
+        assert_eq!(boolean_query(r#"a "b c"#), r#"+a* +"b c""#);
+    }
+
+    #[test]
+    fn needs_an_included_term() {
+        for s in ["", "   ", "-draft", r#"-"a b" -c"#, "+ * ()"] {
+            assert!(SearchQuery::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn highlights_case_insensitively() {
+        let highlight = Highlight::new("Weekly REPORT, weekly", &["weekly", "report"], None);
+        assert_eq!(highlight.text, "Weekly REPORT, weekly");
+        assert_eq!(highlight.ranges, [(0, 6), (7, 13), (15, 21)]);
+    }
+
+    #[test]
+    fn snippet_is_cut_around_the_first_match() {
+        let text = format!("{}match{}", "a".repeat(100), "b".repeat(100));
+        let highlight = Highlight::new(&text, &["match"], Some(20));
+
+        assert_eq!(
+            highlight.text,
+            format!("…{}match{}…", "a".repeat(5), "b".repeat(10))
+        );
+        assert_eq!(highlight.ranges, [(6, 11)]);
+    }
+}
@@ -1,25 +1,480 @@
 use std::str::FromStr;
 
+use chrono::Datelike;
 use serde::{Deserialize, Serialize};
 use sqlx::{error::BoxDynError, mysql::MySqlValueRef, FromRow, MySql, Type};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VecWithTotal<T: std::fmt::Debug + Clone> {
-    pub total: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
     pub items: Vec<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_counts: Option<Vec<TagCount>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag_id: String,
+    pub name: String,
+    pub count: usize,
 }
 
 #[derive(Debug, Clone, FromRow)]
 pub struct Tag {
     pub id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    /// shared by the members of the workspace, personal when `None`
+    pub workspace_id: Option<Vec<u8>>,
     pub name: String,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
 #[derive(Debug, Clone, FromRow)]
 pub struct TagReq {
+    pub id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub workspace_id: Option<Vec<u8>>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Project {
+    pub id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub name: String,
+    pub description: String,
+    /// `#rrggbb`
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    /// hidden from the project list by default since then
+    pub archived_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+#[derive(Debug, Clone, FromRow)]
+pub struct ProjectReq {
+    pub id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub name: String,
+    pub description: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+}
+
+/// saved combination of task filters, sort and search
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskView {
+    pub id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub name: String,
+    /// JSON of the task list query
+    pub query: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskViewReq {
+    pub id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub name: String,
+    pub query: String,
+}
+
+/// public, read-only link to a task or a view
+#[derive(Debug, Clone, FromRow)]
+pub struct ShareLink {
+    pub token: String,
+    pub author_id: Vec<u8>,
+    pub todo_id: Option<Vec<u8>>,
+    pub view_id: Option<Vec<u8>>,
+    /// never expires when `None`
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+#[derive(Debug, Clone, FromRow)]
+pub struct ShareLinkReq {
+    pub token: String,
+    pub author_id: Vec<u8>,
+    pub todo_id: Option<Vec<u8>>,
+    pub view_id: Option<Vec<u8>>,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+}
+
+/// task on the other side of a dependency
+#[derive(Debug, Clone, FromRow)]
+pub struct LinkedTask {
+    pub id: Vec<u8>,
+    pub title: String,
+    pub state: TaskState,
+}
+
+/// number of direct subtasks of a task
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ChildCount {
+    pub total: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReminderChannel {
+    InApp,
+    Webhook,
+    Email,
+}
+impl FromStr for ReminderChannel {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "in-app" => Ok(ReminderChannel::InApp),
+            "webhook" => Ok(ReminderChannel::Webhook),
+            "email" => Ok(ReminderChannel::Email),
+            _ => Err(()),
+        }
+    }
+}
+impl std::fmt::Display for ReminderChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ReminderChannel::InApp => "in-app",
+            ReminderChannel::Webhook => "webhook",
+            ReminderChannel::Email => "email",
+        };
+        f.write_str(s)
+    }
+}
+impl sqlx::Decode<'_, MySql> for ReminderChannel {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let s = <&str as sqlx::Decode<MySql>>::decode(value)?;
+        ReminderChannel::from_str(s).map_err(|_| "invalid ReminderChannel".into())
+    }
+}
+impl sqlx::Encode<'_, MySql> for ReminderChannel {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        self.to_string().encode_by_ref(buf)
+    }
+}
+impl Type<MySql> for ReminderChannel {
+    fn type_info() -> <MySql as sqlx::Database>::TypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Reminder {
+    pub id: Vec<u8>,
+    pub todo_id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub remind_at: Option<chrono::NaiveDateTime>,
+    pub offset_minutes: Option<i32>,
+    pub channel: ReminderChannel,
+    pub target: Option<String>,
+    pub fired_at: Option<chrono::NaiveDateTime>,
+    pub attempts: i32,
+    pub locked_until: Option<chrono::NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+#[derive(Debug, Clone, FromRow)]
+pub struct ReminderReq {
+    pub id: Vec<u8>,
+    pub todo_id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub remind_at: Option<chrono::NaiveDateTime>,
+    pub offset_minutes: Option<i32>,
+    pub channel: ReminderChannel,
+    pub target: Option<String>,
+}
+/// reminder claimed by the scheduler, with the task it is about
+#[derive(Debug, Clone, FromRow)]
+pub struct DueReminder {
+    #[sqlx(flatten)]
+    pub reminder: Reminder,
+    pub title: String,
+    pub due_date: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationKind {
+    Reminder,
+    Overdue,
+    /// a shared task was changed by someone else
+    TaskChanged,
+}
+impl FromStr for NotificationKind {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reminder" => Ok(NotificationKind::Reminder),
+            "overdue" => Ok(NotificationKind::Overdue),
+            "task-changed" => Ok(NotificationKind::TaskChanged),
+            _ => Err(()),
+        }
+    }
+}
+impl std::fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            NotificationKind::Reminder => "reminder",
+            NotificationKind::Overdue => "overdue",
+            NotificationKind::TaskChanged => "task-changed",
+        };
+        f.write_str(s)
+    }
+}
+impl sqlx::Decode<'_, MySql> for NotificationKind {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let s = <&str as sqlx::Decode<MySql>>::decode(value)?;
+        NotificationKind::from_str(s).map_err(|_| "invalid NotificationKind".into())
+    }
+}
+impl sqlx::Encode<'_, MySql> for NotificationKind {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        self.to_string().encode_by_ref(buf)
+    }
+}
+impl Type<MySql> for NotificationKind {
+    fn type_info() -> <MySql as sqlx::Database>::TypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+}
+
+/// access to a shared task or project, each role granting the ones before it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShareRole {
+    Viewer,
+    Editor,
+    /// the author is always an owner
+    Owner,
+}
+impl FromStr for ShareRole {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(ShareRole::Viewer),
+            "editor" => Ok(ShareRole::Editor),
+            "owner" => Ok(ShareRole::Owner),
+            _ => Err(()),
+        }
+    }
+}
+impl std::fmt::Display for ShareRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ShareRole::Viewer => "viewer",
+            ShareRole::Editor => "editor",
+            ShareRole::Owner => "owner",
+        };
+        f.write_str(s)
+    }
+}
+impl sqlx::Decode<'_, MySql> for ShareRole {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let s = <&str as sqlx::Decode<MySql>>::decode(value)?;
+        ShareRole::from_str(s).map_err(|_| "invalid ShareRole".into())
+    }
+}
+impl sqlx::Encode<'_, MySql> for ShareRole {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        self.to_string().encode_by_ref(buf)
+    }
+}
+impl Type<MySql> for ShareRole {
+    fn type_info() -> <MySql as sqlx::Database>::TypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+}
+
+/// a user a task or a project is shared with
+#[derive(Debug, Clone, FromRow)]
+pub struct Share {
+    pub user_id: Vec<u8>,
+    pub username: Option<String>,
+    pub role: ShareRole,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// role of a member of a workspace, each role granting the ones before it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorkspaceRole {
+    /// edits the tasks of the workspace
+    Member,
+    /// also manages the members and the invitations
+    Admin,
+    /// also manages the owners and deletes the workspace
+    Owner,
+}
+impl FromStr for WorkspaceRole {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(WorkspaceRole::Member),
+            "admin" => Ok(WorkspaceRole::Admin),
+            "owner" => Ok(WorkspaceRole::Owner),
+            _ => Err(()),
+        }
+    }
+}
+impl std::fmt::Display for WorkspaceRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            WorkspaceRole::Member => "member",
+            WorkspaceRole::Admin => "admin",
+            WorkspaceRole::Owner => "owner",
+        };
+        f.write_str(s)
+    }
+}
+impl sqlx::Decode<'_, MySql> for WorkspaceRole {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let s = <&str as sqlx::Decode<MySql>>::decode(value)?;
+        WorkspaceRole::from_str(s).map_err(|_| "invalid WorkspaceRole".into())
+    }
+}
+impl sqlx::Encode<'_, MySql> for WorkspaceRole {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        self.to_string().encode_by_ref(buf)
+    }
+}
+impl Type<MySql> for WorkspaceRole {
+    fn type_info() -> <MySql as sqlx::Database>::TypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Workspace {
     pub id: Vec<u8>,
     pub name: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+
+    /// role of the user, only selected while listing their workspaces
+    #[sqlx(default)]
+    pub role: Option<WorkspaceRole>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WorkspaceMember {
+    pub user_id: Vec<u8>,
+    pub username: Option<String>,
+    pub role: WorkspaceRole,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WorkspaceInvitation {
+    pub token: String,
+    pub workspace_id: Vec<u8>,
+    pub inviter_id: Vec<u8>,
+    pub role: WorkspaceRole,
+    pub expires_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Notification {
+    pub id: Vec<u8>,
+    pub user_id: Vec<u8>,
+    pub kind: NotificationKind,
+    pub todo_id: Option<Vec<u8>>,
+    pub message: String,
+    pub read_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyKey {
+    pub request: String,
+    /// `None` until the request is done
+    pub status: Option<u16>,
+    pub response: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskComment {
+    pub id: Vec<u8>,
+    pub todo_id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub body: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskCommentReq {
+    pub id: Vec<u8>,
+    pub todo_id: Vec<u8>,
+    pub author_id: Vec<u8>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskEventAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+impl FromStr for TaskEventAction {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(TaskEventAction::Create),
+            "update" => Ok(TaskEventAction::Update),
+            "delete" => Ok(TaskEventAction::Delete),
+            "restore" => Ok(TaskEventAction::Restore),
+            _ => Err(()),
+        }
+    }
+}
+impl std::fmt::Display for TaskEventAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TaskEventAction::Create => "create",
+            TaskEventAction::Update => "update",
+            TaskEventAction::Delete => "delete",
+            TaskEventAction::Restore => "restore",
+        };
+        f.write_str(s)
+    }
+}
+impl sqlx::Decode<'_, MySql> for TaskEventAction {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let s = <&str as sqlx::Decode<MySql>>::decode(value)?;
+        TaskEventAction::from_str(s).map_err(|_| "invalid TaskEventAction".into())
+    }
+}
+impl sqlx::Encode<'_, MySql> for TaskEventAction {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        self.to_string().encode_by_ref(buf)
+    }
+}
+impl Type<MySql> for TaskEventAction {
+    fn type_info() -> <MySql as sqlx::Database>::TypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TaskEvent {
+    pub id: Vec<u8>,
+    pub todo_id: Vec<u8>,
+    pub author_id: Option<Vec<u8>>,
+    pub actor_id: Option<Vec<u8>>,
+    pub action: TaskEventAction,
+    /// JSON object of `{ "before": ..., "after": ... }` for each changed field
+    pub changes: String,
+    pub created_at: chrono::NaiveDateTime,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -33,6 +488,9 @@ pub struct User {
     pub updated_at: chrono::NaiveDateTime,
     #[sqlx(default)]
     pub deleted_at: Option<chrono::NaiveDateTime>,
+    /// done tasks untouched for this many days are archived
+    #[sqlx(default)]
+    pub auto_archive_days: Option<i32>,
 }
 #[derive(Debug, Clone, FromRow)]
 pub struct UserReq {
@@ -62,14 +520,15 @@ impl FromStr for TaskState {
         }
     }
 }
-impl ToString for TaskState {
-    fn to_string(&self) -> String {
-        match self {
-            TaskState::Icebox => "icebox".to_string(),
-            TaskState::Todo => "todo".to_string(),
-            TaskState::InProgress => "in-progress".to_string(),
-            TaskState::Done => "done".to_string(),
-        }
+impl std::fmt::Display for TaskState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TaskState::Icebox => "icebox",
+            TaskState::Todo => "todo",
+            TaskState::InProgress => "in-progress",
+            TaskState::Done => "done",
+        };
+        f.write_str(s)
     }
 }
 impl sqlx::Decode<'_, MySql> for TaskState {
@@ -107,13 +566,14 @@ impl FromStr for TaskPriority {
         }
     }
 }
-impl ToString for TaskPriority {
-    fn to_string(&self) -> String {
-        match self {
-            TaskPriority::Low => "low".to_string(),
-            TaskPriority::Medium => "medium".to_string(),
-            TaskPriority::High => "high".to_string(),
-        }
+impl std::fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+        };
+        f.write_str(s)
     }
 }
 impl sqlx::Decode<'_, MySql> for TaskPriority {
@@ -133,28 +593,385 @@ impl Type<MySql> for TaskPriority {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// RRULE-style recurrence such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=10`
+///
+/// `BYDAY` is only allowed with `FREQ=WEEKLY`, and at most one of `UNTIL` (`YYYYMMDD`) and
+/// `COUNT` may be given; `COUNT` counts the occurrences left including the current one
+///
+/// `BYMONTHDAY`, only allowed with `FREQ=MONTHLY` and `FREQ=YEARLY`, is the day the occurrences
+/// fall on, or the last day of the months too short for it; it is taken from the first occurrence
+/// when missing so that a task due on the 31st comes back on the 31st after a short month
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    /// sorted from Monday
+    pub weekdays: Vec<chrono::Weekday>,
+    pub until: Option<chrono::NaiveDate>,
+    pub count: Option<u32>,
+    pub month_day: Option<u32>,
+}
+impl Recurrence {
+    const WEEKDAYS: [(&'static str, chrono::Weekday); 7] = [
+        ("MO", chrono::Weekday::Mon),
+        ("TU", chrono::Weekday::Tue),
+        ("WE", chrono::Weekday::Wed),
+        ("TH", chrono::Weekday::Thu),
+        ("FR", chrono::Weekday::Fri),
+        ("SA", chrono::Weekday::Sat),
+        ("SU", chrono::Weekday::Sun),
+    ];
+
+    /// the occurrence following the one at `from` and the rule it carries, or `None` once the
+    /// rule has run out
+    pub fn next(&self, from: chrono::NaiveDateTime) -> Option<(chrono::NaiveDateTime, Self)> {
+        let count = match self.count {
+            Some(1) => return None,
+            count => count.map(|c| c - 1),
+        };
+
+        let date = from.date();
+        let interval = self.interval.max(1);
+        let month_day = match self.frequency {
+            Frequency::Monthly | Frequency::Yearly => Some(self.month_day.unwrap_or(date.day())),
+            Frequency::Daily | Frequency::Weekly => None,
+        };
+        let next_date = match self.frequency {
+            Frequency::Daily => date.checked_add_signed(chrono::Duration::days(interval as i64)),
+            Frequency::Weekly if !self.weekdays.is_empty() => {
+                let weekday = date.weekday().num_days_from_monday();
+                match self
+                    .weekdays
+                    .iter()
+                    .map(|w| w.num_days_from_monday())
+                    .find(|w| *w > weekday)
+                {
+                    Some(w) => {
+                        date.checked_add_signed(chrono::Duration::days((w - weekday) as i64))
+                    }
+                    None => {
+                        let first = self.weekdays[0].num_days_from_monday();
+                        let days = 7 * interval as i64 - weekday as i64 + first as i64;
+                        date.checked_add_signed(chrono::Duration::days(days))
+                    }
+                }
+            }
+            Frequency::Weekly => date.checked_add_signed(chrono::Duration::weeks(interval as i64)),
+            Frequency::Monthly => Self::add_months(date, interval, month_day),
+            Frequency::Yearly => Self::add_months(date, interval.checked_mul(12)?, month_day),
+        }?;
+
+        if matches!(self.until, Some(until) if next_date > until) {
+            return None;
+        }
+
+        let rule = Self {
+            count,
+            month_day,
+            ..self.clone()
+        };
+        Some((next_date.and_time(from.time()), rule))
+    }
+
+    /// `months` after `date`, on `day` or on the last day of the month when it is shorter
+    fn add_months(
+        date: chrono::NaiveDate,
+        months: u32,
+        day: Option<u32>,
+    ) -> Option<chrono::NaiveDate> {
+        let first = date
+            .with_day(1)?
+            .checked_add_months(chrono::Months::new(months))?;
+        let last_day = first
+            .checked_add_months(chrono::Months::new(1))?
+            .pred_opt()?
+            .day();
+        first.with_day(day.unwrap_or(date.day()).min(last_day))
+    }
+}
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().strip_prefix("RRULE:").unwrap_or(s.trim());
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut weekdays = Vec::new();
+        let mut until = None;
+        let mut count = None;
+        let mut month_day = None;
+
+        for part in s.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid rule part: {}", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => anyhow::bail!("Invalid FREQ: {}", value),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|i| *i > 0)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid INTERVAL: {}", value))?
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let weekday = Self::WEEKDAYS
+                            .iter()
+                            .find(|(name, _)| name.eq_ignore_ascii_case(day.trim()))
+                            .map(|(_, weekday)| *weekday)
+                            .ok_or_else(|| anyhow::anyhow!("Invalid BYDAY: {}", day))?;
+                        if !weekdays.contains(&weekday) {
+                            weekdays.push(weekday);
+                        }
+                    }
+                    weekdays.sort_by_key(|w| w.num_days_from_monday());
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    until = Some(
+                        chrono::NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .or_else(|_| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+                            .map_err(|_| anyhow::anyhow!("Invalid UNTIL: {}", value))?,
+                    )
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|c| *c > 0)
+                            .ok_or_else(|| anyhow::anyhow!("Invalid COUNT: {}", value))?,
+                    )
+                }
+                "BYMONTHDAY" => {
+                    month_day = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|d| (1..=31).contains(d))
+                            .ok_or_else(|| anyhow::anyhow!("Invalid BYMONTHDAY: {}", value))?,
+                    )
+                }
+                _ => anyhow::bail!("Unsupported rule part: {}", key),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| anyhow::anyhow!("FREQ is required"))?;
+        if !weekdays.is_empty() && frequency != Frequency::Weekly {
+            anyhow::bail!("BYDAY is only supported with FREQ=WEEKLY");
+        }
+        if month_day.is_some() && !matches!(frequency, Frequency::Monthly | Frequency::Yearly) {
+            anyhow::bail!("BYMONTHDAY is only supported with FREQ=MONTHLY or FREQ=YEARLY");
+        }
+        if until.is_some() && count.is_some() {
+            anyhow::bail!("UNTIL and COUNT cannot be used together");
+        }
+
+        Ok(Self {
+            frequency,
+            interval,
+            weekdays,
+            until,
+            count,
+            month_day,
+        })
+    }
+}
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={};INTERVAL={}", frequency, self.interval)?;
+        if !self.weekdays.is_empty() {
+            let days = self
+                .weekdays
+                .iter()
+                .filter_map(|w| Self::WEEKDAYS.iter().find(|(_, weekday)| weekday == w))
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(month_day) = self.month_day {
+            write!(f, ";BYMONTHDAY={}", month_day)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        Ok(())
+    }
+}
+impl Serialize for Recurrence {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Recurrence::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+impl sqlx::Decode<'_, MySql> for Recurrence {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let s = <&str as sqlx::Decode<MySql>>::decode(value)?;
+        Recurrence::from_str(s).map_err(|e| e.into())
+    }
+}
+impl sqlx::Encode<'_, MySql> for Recurrence {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        self.to_string().encode_by_ref(buf)
+    }
+}
+impl Type<MySql> for Recurrence {
+    fn type_info() -> <MySql as sqlx::Database>::TypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+}
+
 #[derive(Debug, Clone, FromRow)]
 pub struct Todo {
     pub id: Vec<u8>,
     pub author_id: Option<Vec<u8>>,
+    /// the one doing the task, who may differ from its author
+    pub assignee_id: Option<Vec<u8>>,
+    pub parent_id: Option<Vec<u8>>,
+    /// in the inbox when `None`
+    pub project_id: Option<Vec<u8>>,
+    /// seen by the members of the workspace, personal when `None`
+    pub workspace_id: Option<Vec<u8>>,
     pub title: String,
     pub description: String,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// bumped by every update, exposed as the ETag of the task
+    pub version: u32,
 
     pub state: TaskState,
     pub priority: Option<TaskPriority>,
     pub due_date: Option<chrono::NaiveDateTime>,
+    pub recurrence: Option<Recurrence>,
+    /// hidden from the task list by default since then
+    pub archived_at: Option<chrono::NaiveDateTime>,
+    /// in the trash since then
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+
+    /// relevance to the full-text search, only selected while searching
+    #[sqlx(default)]
+    pub score: Option<f64>,
 }
 
 #[derive(Debug, Clone, FromRow)]
 pub struct TodoReq {
     pub id: Vec<u8>,
     pub author_id: Option<Vec<u8>>,
+    pub assignee_id: Option<Vec<u8>>,
+    pub parent_id: Option<Vec<u8>>,
+    pub project_id: Option<Vec<u8>>,
+    pub workspace_id: Option<Vec<u8>>,
     pub title: String,
     pub description: String,
 
     pub state: TaskState,
     pub priority: Option<TaskPriority>,
     pub due_date: Option<chrono::NaiveDateTime>,
+    pub recurrence: Option<Recurrence>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences(rule: &str, from: &str, n: usize) -> Vec<String> {
+        let mut rule = Recurrence::from_str(rule).unwrap();
+        let mut at = chrono::NaiveDate::parse_from_str(from, "%Y-%m-%d")
+            .unwrap()
+            .and_hms(9, 0, 0);
+        let mut dates = Vec::new();
+        for _ in 0..n {
+            let Some((next, next_rule)) = rule.next(at) else {
+                break;
+            };
+            dates.push(next.format("%Y-%m-%d").to_string());
+            (at, rule) = (next, next_rule);
+        }
+        dates
+    }
+
+    #[test]
+    fn monthly_keeps_the_end_of_the_month() {
+        assert_eq!(
+            occurrences("FREQ=MONTHLY", "2026-01-31", 4),
+            ["2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31"]
+        );
+        assert_eq!(
+            occurrences("FREQ=MONTHLY;INTERVAL=2;BYMONTHDAY=30", "2027-12-30", 2),
+            ["2028-02-29", "2028-04-30"]
+        );
+    }
+
+    #[test]
+    fn yearly_keeps_leap_days() {
+        assert_eq!(
+            occurrences("FREQ=YEARLY", "2028-02-29", 4),
+            ["2029-02-28", "2030-02-28", "2031-02-28", "2032-02-29"]
+        );
+    }
+
+    #[test]
+    fn next_rule_carries_the_day() {
+        let rule = Recurrence::from_str("FREQ=MONTHLY;COUNT=3").unwrap();
+        let from = chrono::NaiveDate::from_ymd(2026, 1, 31).and_hms(9, 0, 0);
+        let (_, rule) = rule.next(from).unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=MONTHLY;INTERVAL=1;BYMONTHDAY=31;COUNT=2"
+        );
+        assert_eq!(Recurrence::from_str(&rule.to_string()).unwrap(), rule);
+    }
+
+    #[test]
+    fn huge_intervals_run_out() {
+        for rule in [
+            "FREQ=DAILY;INTERVAL=4294967295",
+            "FREQ=WEEKLY;INTERVAL=4294967295",
+            "FREQ=WEEKLY;INTERVAL=4294967295;BYDAY=MO",
+            "FREQ=MONTHLY;INTERVAL=4294967295",
+            "FREQ=YEARLY;INTERVAL=400000000",
+            "FREQ=YEARLY;INTERVAL=4294967295",
+        ] {
+            assert!(occurrences(rule, "2026-10-18", 1).is_empty(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn month_day_needs_monthly_or_yearly() {
+        assert!(Recurrence::from_str("FREQ=WEEKLY;BYMONTHDAY=3").is_err());
+        assert!(Recurrence::from_str("FREQ=MONTHLY;BYMONTHDAY=32").is_err());
+        assert!(Recurrence::from_str("FREQ=YEARLY;BYMONTHDAY=0").is_err());
+    }
 }
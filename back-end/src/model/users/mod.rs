@@ -190,3 +190,21 @@ pub async fn is_valid_id(
         .get::<i32, _>(0);
     Ok(count > 0)
 }
+
+/// archives the done tasks of the user untouched for `days` days, or never with `None`
+pub async fn set_auto_archive_days(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    days: Option<i32>,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "UPDATE `users` SET `auto_archive_days` = ? WHERE `id` = ?;";
+    let bin_id = ulid_to_binary(id);
+    sqlx::query(query)
+        .bind(days)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use sqlx::{mysql::MySqlArguments, Acquire, MySql, Row};
+
+use super::Update;
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+pub async fn get_views(
+    conn: impl Acquire<'_, Database = MySql>,
+    author_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::TaskView>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `task_views` WHERE `author_id` = ? ORDER BY `name` ASC;";
+
+    let bin_author_id = ulid_to_binary(author_id);
+
+    let rows = sqlx::query_as::<_, types::TaskView>(query)
+        .bind(bin_author_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_view(
+    conn: impl Acquire<'_, Database = MySql>,
+    view_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::TaskView>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `task_views` WHERE `id` = ?;";
+
+    let bin_view_id = ulid_to_binary(view_id);
+
+    let row = sqlx::query_as::<_, types::TaskView>(query)
+        .bind(bin_view_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn get_view_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    view_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::TaskView>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `task_views` WHERE `id` = ? FOR UPDATE;";
+
+    let bin_view_id = ulid_to_binary(view_id);
+
+    let row = sqlx::query_as::<_, types::TaskView>(query)
+        .bind(bin_view_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn is_view_name_exists(
+    conn: impl Acquire<'_, Database = MySql>,
+    author_id: ulid::Ulid,
+    name: &str,
+) -> anyhow::Result<bool> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT COUNT(*) FROM `task_views` WHERE `author_id` = ? AND `name` = ?;";
+
+    let bin_author_id = ulid_to_binary(author_id);
+
+    let count = sqlx::query(query)
+        .bind(bin_author_id.as_slice())
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(count > 0)
+}
+
+pub async fn insert_view(
+    conn: impl Acquire<'_, Database = MySql>,
+    view: types::TaskViewReq,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "INSERT INTO `task_views` (`id`, `author_id`, `name`, `query`) VALUES (?, ?, ?, ?);";
+
+    sqlx::query(query)
+        .bind(view.id)
+        .bind(view.author_id)
+        .bind(view.name)
+        .bind(view.query)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateView {
+    pub name: Update<String>,
+    pub query: Update<String>,
+}
+impl UpdateView {
+    fn to_prepared_query(&self) -> String {
+        let mut query = Vec::new();
+
+        if let Some(q) = self.name.to_prepared_query("name") {
+            query.push(q);
+        }
+        if let Some(q) = self.query.to_prepared_query("query") {
+            query.push(q);
+        }
+
+        query.join(", ")
+    }
+
+    pub fn bind_query<'a>(
+        &'a self,
+        query: sqlx::query::Query<'a, sqlx::MySql, MySqlArguments>,
+    ) -> sqlx::query::Query<'a, sqlx::MySql, MySqlArguments> {
+        let mut query = self.name.bind_query(query);
+        query = self.query.bind_query(query);
+
+        query
+    }
+
+    pub fn is_nop(&self) -> bool {
+        self.name.is_nop() && self.query.is_nop()
+    }
+}
+
+pub async fn update_view(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    update: UpdateView,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    if update.is_nop() {
+        return Ok(());
+    }
+
+    let query = format!(
+        "UPDATE `task_views` SET {} WHERE `id` = ?;",
+        update.to_prepared_query()
+    );
+
+    let bin_id = ulid_to_binary(id);
+
+    let building_query = update
+        .bind_query(sqlx::query(query.as_str()))
+        .bind(bin_id.as_slice());
+
+    building_query.execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+pub async fn delete_view(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `task_views` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,347 @@
+use sqlx::{Acquire, MySql, Row};
+
+use crate::utils::ulid_to_binary;
+
+use super::types;
+
+/// workspaces the user is a member of, with their role, sorted by name
+pub async fn get_workspaces(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::Workspace>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT `workspaces`.*, `workspace_members`.`role` FROM `workspaces`
+            INNER JOIN `workspace_members`
+                ON `workspace_members`.`workspace_id` = `workspaces`.`id`
+            WHERE `workspace_members`.`user_id` = ?
+            ORDER BY `workspaces`.`name` ASC;"#;
+
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let rows = sqlx::query_as::<_, types::Workspace>(query)
+        .bind(bin_user_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_workspace(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Workspace>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `workspaces` WHERE `id` = ?;";
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+
+    let row = sqlx::query_as::<_, types::Workspace>(query)
+        .bind(bin_workspace_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn get_workspace_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::Workspace>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "SELECT * FROM `workspaces` WHERE `id` = ? FOR UPDATE;";
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+
+    let row = sqlx::query_as::<_, types::Workspace>(query)
+        .bind(bin_workspace_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+/// role of the user in the workspace, `None` unless they are a member
+pub async fn get_workspace_role(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<Option<types::WorkspaceRole>> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "SELECT `role` FROM `workspace_members` WHERE `workspace_id` = ? AND `user_id` = ?;";
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    let row = sqlx::query(query)
+        .bind(bin_workspace_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    let role = row
+        .map(|row| row.try_get::<types::WorkspaceRole, _>(0))
+        .transpose()?;
+
+    Ok(role)
+}
+
+/// creates the workspace with `owner_id` as its first owner
+pub async fn insert_workspace(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    name: &str,
+    owner_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "INSERT INTO `workspaces` (`id`, `name`) VALUES (?, ?);";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .bind(name)
+        .execute(&mut *conn)
+        .await?;
+
+    set_member_role(&mut *conn, id, owner_id, types::WorkspaceRole::Owner).await?;
+
+    Ok(())
+}
+
+pub async fn rename_workspace(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+    name: &str,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "UPDATE `workspaces` SET `name` = ? WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(name)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// the tasks and the tags of the workspace are deleted along with it
+pub async fn delete_workspace(
+    conn: impl Acquire<'_, Database = MySql>,
+    id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `workspaces` WHERE `id` = ?;";
+
+    let bin_id = ulid_to_binary(id);
+
+    sqlx::query(query)
+        .bind(bin_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// sorted by username
+pub async fn get_members(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::WorkspaceMember>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT `workspace_members`.`user_id`, `users`.`username`, `workspace_members`.`role`, `workspace_members`.`created_at`
+            FROM `workspace_members`
+            INNER JOIN `users` ON `users`.`id` = `workspace_members`.`user_id`
+            WHERE `workspace_members`.`workspace_id` = ?
+            ORDER BY `users`.`username` ASC;"#;
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+
+    let rows = sqlx::query_as::<_, types::WorkspaceMember>(query)
+        .bind(bin_workspace_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+/// adds the user to the workspace, or changes the role they have
+pub async fn set_member_role(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+    role: types::WorkspaceRole,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `workspace_members` (`workspace_id`, `user_id`, `role`) VALUES (?, ?, ?)
+            ON DUPLICATE KEY UPDATE `role` = VALUES(`role`);"#;
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_workspace_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .bind(role)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn remove_member(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+    user_id: ulid::Ulid,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `workspace_members` WHERE `workspace_id` = ? AND `user_id` = ?;";
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+    let bin_user_id = ulid_to_binary(user_id);
+
+    sqlx::query(query)
+        .bind(bin_workspace_id.as_slice())
+        .bind(bin_user_id.as_slice())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn count_owners(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+) -> anyhow::Result<usize> {
+    let mut conn = conn.acquire().await?;
+
+    let query =
+        "SELECT COUNT(*) FROM `workspace_members` WHERE `workspace_id` = ? AND `role` = 'owner';";
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+
+    let count = sqlx::query(query)
+        .bind(bin_workspace_id.as_slice())
+        .fetch_one(&mut *conn)
+        .await?
+        .get::<i64, _>(0);
+
+    Ok(count as usize)
+}
+
+pub async fn insert_invitation(
+    conn: impl Acquire<'_, Database = MySql>,
+    token: &str,
+    workspace_id: ulid::Ulid,
+    inviter_id: ulid::Ulid,
+    role: types::WorkspaceRole,
+    expires_at: chrono::NaiveDateTime,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        INSERT INTO `workspace_invitations` (`token`, `workspace_id`, `inviter_id`, `role`, `expires_at`)
+            VALUES (?, ?, ?, ?, ?);"#;
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+    let bin_inviter_id = ulid_to_binary(inviter_id);
+
+    sqlx::query(query)
+        .bind(token)
+        .bind(bin_workspace_id.as_slice())
+        .bind(bin_inviter_id.as_slice())
+        .bind(role)
+        .bind(expires_at)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// pending invitations of the workspace, newest first
+pub async fn get_invitations(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+) -> anyhow::Result<Vec<types::WorkspaceInvitation>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `workspace_invitations`
+            WHERE `workspace_id` = ? AND `expires_at` > NOW()
+            ORDER BY `created_at` DESC;"#;
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+
+    let rows = sqlx::query_as::<_, types::WorkspaceInvitation>(query)
+        .bind(bin_workspace_id.as_slice())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows)
+}
+
+/// the pending invitation to the workspace, `None` once expired
+pub async fn get_invitation_with_lock(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_id: ulid::Ulid,
+    token: &str,
+) -> anyhow::Result<Option<types::WorkspaceInvitation>> {
+    let mut conn = conn.acquire().await?;
+
+    let query = r#"
+        SELECT * FROM `workspace_invitations`
+            WHERE `token` = ? AND `workspace_id` = ? AND `expires_at` > NOW()
+            FOR UPDATE;"#;
+
+    let bin_workspace_id = ulid_to_binary(workspace_id);
+
+    let row = sqlx::query_as::<_, types::WorkspaceInvitation>(query)
+        .bind(token)
+        .bind(bin_workspace_id.as_slice())
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+pub async fn delete_invitation(
+    conn: impl Acquire<'_, Database = MySql>,
+    token: &str,
+) -> anyhow::Result<()> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `workspace_invitations` WHERE `token` = ?;";
+
+    sqlx::query(query).bind(token).execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// forgets the expired invitations, returning how many were forgotten
+pub async fn purge_expired_invitations(
+    conn: impl Acquire<'_, Database = MySql>,
+) -> anyhow::Result<u64> {
+    let mut conn = conn.acquire().await?;
+
+    let query = "DELETE FROM `workspace_invitations` WHERE `expires_at` <= NOW();";
+
+    let result = sqlx::query(query).execute(&mut *conn).await?;
+
+    Ok(result.rows_affected())
+}
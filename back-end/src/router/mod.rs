@@ -1,2 +1,7 @@
 pub mod account;
+pub mod notification;
+pub mod project;
+pub mod share_link;
+pub mod tag;
 pub mod task;
+pub mod workspace;
@@ -0,0 +1,238 @@
+use actix_session::Session;
+use actix_web::{
+    delete, dev::HttpServiceFactory, get, put, web, HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{
+        self,
+        types::{Notification, NotificationKind, VecWithTotal},
+    },
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+pub fn notifications_router() -> impl HttpServiceFactory {
+    web::scope("/notifications")
+        .service(get_notifications_me)
+        .service(put_notifications_me_read)
+        .service(put_notification_read)
+        .service(delete_notification)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationResponse {
+    pub id: String,
+    pub kind: NotificationKind,
+    pub task_id: Option<String>,
+    pub message: String,
+    pub read_at: Option<String>,
+    pub created_at: String,
+}
+impl TryFrom<Notification> for NotificationResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Notification) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let task_id = value
+            .todo_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let read_at = value
+            .read_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            kind: value.kind,
+            task_id: task_id.map(|id| id.to_string()),
+            message: value.message,
+            read_at,
+            created_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetNotificationQuery {
+    /// include the notifications already read, defaults to `false`
+    all: Option<bool>,
+    /// defaults to 50
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+#[get("/me")]
+pub async fn get_notifications_me(
+    _req: HttpRequest,
+    query: web::Query<GetNotificationQuery>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_notifications_me_inner(
+        _req: HttpRequest,
+        query: web::Query<GetNotificationQuery>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let notifications = model::notifications::get_notifications(
+            pool.as_ref(),
+            user_ulid,
+            !query.all.unwrap_or(false),
+            query.limit.unwrap_or(50),
+            query.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let items = notifications
+            .items
+            .into_iter()
+            .map(NotificationResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(VecWithTotal {
+            total: notifications.total,
+            items,
+            tag_counts: None,
+            next_cursor: None,
+            prev_cursor: None,
+        }))
+    }
+
+    get_notifications_me_inner(_req, query, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[put("/me/read")]
+pub async fn put_notifications_me_read(
+    _req: HttpRequest,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_notifications_me_read_inner(
+        _req: HttpRequest,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        model::notifications::mark_all_notifications_read(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_notifications_me_read_inner(_req, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// `Not Found` unless the notification belongs to the user
+async fn get_own_notification(
+    conn: impl sqlx::Acquire<'_, Database = sqlx::MySql>,
+    user_ulid: ulid::Ulid,
+    notification_ulid: ulid::Ulid,
+) -> Result<Notification, HttpResponse> {
+    let notification = model::notifications::get_notification(conn, notification_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    if notification.user_id != ulid_to_binary(user_ulid).to_vec() {
+        return Err(HttpResponse::NotFound().body("Not Found"));
+    }
+
+    Ok(notification)
+}
+
+#[put("/{id}/read")]
+pub async fn put_notification_read(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_notification_read_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let notification_ulid = ulid::Ulid::from_string(&id).map_err(|e| {
+            HttpResponse::BadRequest().body(format!("Invalid notification id: {}", e))
+        })?;
+
+        get_own_notification(pool.as_ref(), user_ulid, notification_ulid).await?;
+
+        model::notifications::mark_notification_read(pool.as_ref(), notification_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_notification_read_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[delete("/{id}")]
+pub async fn delete_notification(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_notification_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let notification_ulid = ulid::Ulid::from_string(&id).map_err(|e| {
+            HttpResponse::BadRequest().body(format!("Invalid notification id: {}", e))
+        })?;
+
+        get_own_notification(pool.as_ref(), user_ulid, notification_ulid).await?;
+
+        model::notifications::delete_notification(pool.as_ref(), notification_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_notification_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
@@ -0,0 +1,422 @@
+mod share;
+
+use actix_session::Session;
+use actix_web::{
+    delete, dev::HttpServiceFactory, get, patch, post, web, HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, MySql};
+
+use crate::{
+    model::{
+        self,
+        types::{Project, ProjectReq, ShareRole},
+        Update,
+    },
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+/// tasks without a project are in the inbox, listed with `GET /tasks/me?project=inbox`
+pub fn projects_router() -> impl HttpServiceFactory {
+    web::scope("/projects")
+        .service(post_project)
+        .service(get_projects_me)
+        .service(get_project)
+        .service(patch_project)
+        .service(delete_project)
+        .service(share::get_project_shares)
+        .service(share::put_project_share)
+        .service(share::delete_project_share)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectResponse {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub archived_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+impl TryFrom<Project> for ProjectResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Project) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let archived_at = value
+            .archived_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            name: value.name,
+            description: value.description,
+            color: value.color,
+            icon: value.icon,
+            archived_at,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+/// loads the project and checks that `user_ulid` has at least `role` on it, as its author or
+/// through a share
+pub(crate) async fn get_permitted_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    project_ulid: ulid::Ulid,
+    role: ShareRole,
+    with_lock: bool,
+) -> Result<Project, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let project = if with_lock {
+        model::projects::get_project_with_lock(&mut *conn, project_ulid).await
+    } else {
+        model::projects::get_project(&mut *conn, project_ulid).await
+    }
+    .map_err(|e| HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e)))?
+    .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    let project_role = model::shares::get_project_role(&mut *conn, user_ulid, &project)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if project_role < Some(role) {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(project)
+}
+
+/// `#rrggbb`
+fn is_valid_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetProjectsQuery {
+    /// list archived projects along with the others, defaults to `false`
+    include_archived: Option<bool>,
+}
+#[get("/me")]
+pub async fn get_projects_me(
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+    query: web::Query<GetProjectsQuery>,
+) -> impl Responder {
+    async fn get_projects_me_inner(
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+        query: web::Query<GetProjectsQuery>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let projects = model::projects::get_projects(
+            pool.as_ref(),
+            user_ulid,
+            query.include_archived.unwrap_or(false),
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .into_iter()
+        .map(ProjectResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Ok().json(projects))
+    }
+
+    get_projects_me_inner(session, pool, query)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[get("/{id}")]
+pub async fn get_project(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_project_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let project_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        let project = get_permitted_project(
+            pool.as_ref(),
+            user_ulid,
+            project_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let project = ProjectResponse::try_from(project).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Ok().json(project))
+    }
+
+    get_project_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostProjectRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// `#rrggbb`
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostProjectResponse {
+    pub id: String,
+}
+#[post("")]
+pub async fn post_project(
+    _req: HttpRequest,
+    body: web::Json<PostProjectRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_project_inner(
+        _req: HttpRequest,
+        body: web::Json<PostProjectRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let name = body.name.trim().to_string();
+        if name.is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid project name"));
+        }
+        if matches!(&body.color, Some(color) if !is_valid_color(color)) {
+            return Err(HttpResponse::BadRequest().body("Invalid project color"));
+        }
+
+        let is_exists = model::projects::is_project_name_exists(&mut tx, user_ulid, &name)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        if is_exists {
+            return Err(HttpResponse::BadRequest().body("Project already exists"));
+        }
+
+        let project_ulid = ulid::Ulid::new();
+
+        model::projects::insert_project(
+            &mut tx,
+            ProjectReq {
+                id: ulid_to_binary(project_ulid).to_vec(),
+                author_id: ulid_to_binary(user_ulid).to_vec(),
+                name,
+                description: body.description.clone(),
+                color: body.color.clone(),
+                icon: body.icon.clone(),
+            },
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().json(PostProjectResponse {
+            id: project_ulid.to_string(),
+        }))
+    }
+
+    post_project_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatchProjectRequest {
+    #[serde(default)]
+    pub name: Update<String>,
+    #[serde(default)]
+    pub description: Update<String>,
+    #[serde(default)]
+    pub color: Update<Option<String>>,
+    #[serde(default)]
+    pub icon: Update<Option<String>>,
+    #[serde(default)]
+    pub archived: Update<bool>,
+}
+#[patch("/{id}")]
+pub async fn patch_project(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PatchProjectRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn patch_project_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PatchProjectRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let project_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        let project =
+            get_permitted_project(&mut tx, user_ulid, project_ulid, ShareRole::Editor, true)
+                .await?;
+        // names are unique among the projects of the author, whoever renames it
+        let author_ulid = binary_to_ulid(project.author_id.as_slice()).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let name = body.name.clone().map(|name| name.trim().to_string());
+        if let Update::Set(name) = &name {
+            if name.is_empty() {
+                return Err(HttpResponse::BadRequest().body("Invalid project name"));
+            }
+            if name != &project.name {
+                let is_exists = model::projects::is_project_name_exists(&mut tx, author_ulid, name)
+                    .await
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+                if is_exists {
+                    return Err(HttpResponse::BadRequest().body("Project already exists"));
+                }
+            }
+        }
+        if matches!(&body.color, Update::Set(Some(color)) if !is_valid_color(color)) {
+            return Err(HttpResponse::BadRequest().body("Invalid project color"));
+        }
+
+        model::projects::update_project(
+            &mut tx,
+            project_ulid,
+            model::projects::UpdateProject {
+                name,
+                description: body.description.clone(),
+                color: body.color.clone(),
+                icon: body.icon.clone(),
+            },
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        if let Update::Set(archived) = body.archived {
+            model::projects::set_archived(&mut tx, project_ulid, archived)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    patch_project_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// the tasks of the project go back to the inbox
+#[delete("/{id}")]
+pub async fn delete_project(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_project_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let project_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        get_permitted_project(&mut tx, user_ulid, project_ulid, ShareRole::Owner, true).await?;
+
+        model::projects::delete_project(&mut tx, project_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_project_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
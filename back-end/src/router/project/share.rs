@@ -0,0 +1,174 @@
+use actix_session::Session;
+use actix_web::{delete, get, put, web, HttpRequest, HttpResponse, Responder};
+
+use super::get_permitted_project;
+use crate::{
+    model::{self, types::ShareRole},
+    router::task::share::{get_share_user, PutShareRequest, ShareResponse},
+    utils::{binary_to_ulid, check_is_logged_in},
+};
+
+#[get("/{id}/shares")]
+pub async fn get_project_shares(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_project_shares_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let project_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        get_permitted_project(
+            pool.as_ref(),
+            user_ulid,
+            project_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let shares = model::shares::get_project_shares(pool.as_ref(), project_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(ShareResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(shares))
+    }
+
+    get_project_shares_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// only the owners of the project share it, replacing the role the user had
+#[put("/{id}/shares/{username}")]
+pub async fn put_project_share(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    body: web::Json<PutShareRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_project_share_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        body: web::Json<PutShareRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, username) = path.into_inner();
+        let project_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        let project =
+            get_permitted_project(&mut tx, user_ulid, project_ulid, ShareRole::Owner, true).await?;
+
+        let target = get_share_user(&mut tx, &username, false).await?;
+        if project.author_id == target.id {
+            return Err(HttpResponse::BadRequest().body("Cannot share with the author"));
+        }
+        let target_ulid = binary_to_ulid(target.id.as_slice()).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+        if target_ulid == user_ulid {
+            return Err(HttpResponse::BadRequest().body("Cannot share with yourself"));
+        }
+
+        model::shares::share_project(&mut tx, project_ulid, target_ulid, body.role)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_project_share_inner(_req, path, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// only the owners of the project unshare it, except for the users leaving it themselves
+#[delete("/{id}/shares/{username}")]
+pub async fn delete_project_share(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_project_share_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, username) = path.into_inner();
+        let project_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        let target = get_share_user(&mut tx, &username, true).await?;
+        let target_ulid = binary_to_ulid(target.id.as_slice()).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let role = if target_ulid == user_ulid {
+            ShareRole::Viewer
+        } else {
+            ShareRole::Owner
+        };
+        get_permitted_project(&mut tx, user_ulid, project_ulid, role, true).await?;
+
+        model::shares::unshare_project(&mut tx, project_ulid, target_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_project_share_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
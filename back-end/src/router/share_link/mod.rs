@@ -0,0 +1,329 @@
+use actix_session::Session;
+use actix_web::{
+    delete, dev::HttpServiceFactory, get, post, web, HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{
+        self,
+        types::{ShareLink, ShareLinkReq, ShareRole},
+    },
+    router::task::{
+        get_permitted_task, to_task_response,
+        view::{get_own_view, list_view_tasks, GetViewTasksQuery},
+    },
+    utils::{binary_to_ulid, check_is_logged_in, generate_token, ulid_to_binary},
+};
+
+/// read-only links to a task or a view, opened without an account by anyone holding the token
+pub fn share_links_router() -> impl HttpServiceFactory {
+    web::scope("/share")
+        .service(post_share_link)
+        .service(get_share_links_me)
+        .service(get_shared)
+        .service(delete_share_link)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareLinkResponse {
+    pub token: String,
+    pub task_id: Option<String>,
+    pub view_id: Option<String>,
+    /// never expires when `None`
+    pub expires_at: Option<String>,
+    pub created_at: String,
+}
+impl TryFrom<ShareLink> for ShareLinkResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ShareLink) -> Result<Self, Self::Error> {
+        let task_id = value
+            .todo_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let view_id = value
+            .view_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let expires_at = value
+            .expires_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            token: value.token,
+            task_id: task_id.map(|id| id.to_string()),
+            view_id: view_id.map(|id| id.to_string()),
+            expires_at,
+            created_at,
+        })
+    }
+}
+
+#[get("/me")]
+pub async fn get_share_links_me(
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_share_links_me_inner(
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let links = model::share_links::get_share_links(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(ShareLinkResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(links))
+    }
+
+    get_share_links_me_inner(session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostShareLinkRequest {
+    /// exactly one of `task_id` and `view_id`
+    #[serde(default)]
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub view_id: Option<String>,
+    /// at most 365, never expires when absent
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+}
+/// links a task its owners share, or a view of its author
+#[post("")]
+pub async fn post_share_link(
+    _req: HttpRequest,
+    body: web::Json<PostShareLinkRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_share_link_inner(
+        _req: HttpRequest,
+        body: web::Json<PostShareLinkRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (todo_id, view_id) = match (&body.task_id, &body.view_id) {
+            (Some(task_id), None) => {
+                let task_ulid = ulid::Ulid::from_string(task_id).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid task id: {}", e))
+                })?;
+                let task =
+                    get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Owner, false)
+                        .await?;
+                (Some(task.id), None)
+            }
+            (None, Some(view_id)) => {
+                let view_ulid = ulid::Ulid::from_string(view_id).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid view id: {}", e))
+                })?;
+                let view = get_own_view(&mut tx, user_ulid, view_ulid, false).await?;
+                (None, Some(view.id))
+            }
+            _ => {
+                return Err(
+                    HttpResponse::BadRequest().body("Invalid share link: task_id or view_id")
+                )
+            }
+        };
+
+        let expires_at = match body.expires_in_days {
+            Some(expires_in_days) if !(1..=365).contains(&expires_in_days) => {
+                return Err(HttpResponse::BadRequest().body("Invalid expires_in_days"));
+            }
+            Some(expires_in_days) => {
+                Some(chrono::Utc::now().naive_utc() + chrono::Duration::days(expires_in_days))
+            }
+            None => None,
+        };
+
+        let link = ShareLinkReq {
+            token: generate_token(),
+            author_id: ulid_to_binary(user_ulid).to_vec(),
+            todo_id,
+            view_id,
+            expires_at,
+        };
+
+        model::share_links::insert_share_link(&mut tx, link.clone())
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let link = ShareLinkResponse::try_from(ShareLink {
+            token: link.token,
+            author_id: link.author_id,
+            todo_id: link.todo_id,
+            view_id: link.view_id,
+            expires_at: link.expires_at,
+            created_at: chrono::Utc::now().naive_utc(),
+        })
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().json(link))
+    }
+
+    post_share_link_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// revokes the link
+#[delete("/{token}")]
+pub async fn delete_share_link(
+    _req: HttpRequest,
+    token: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_share_link_inner(
+        _req: HttpRequest,
+        token: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let link = model::share_links::get_share_link_with_lock(&mut tx, &token)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+        if link.author_id != ulid_to_binary(user_ulid).to_vec() {
+            return Err(HttpResponse::Forbidden().body("Forbidden"));
+        }
+
+        model::share_links::delete_share_link(&mut tx, &token)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_share_link_inner(_req, token, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// no session needed: the task, or the tasks of the view paginated like `/tasks/views/{id}`,
+/// as their author sees them, without the ids of the users
+#[get("/{token}")]
+pub async fn get_shared(
+    token: web::Path<String>,
+    query: web::Query<GetViewTasksQuery>,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_shared_inner(
+        token: web::Path<String>,
+        query: web::Query<GetViewTasksQuery>,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let link = model::share_links::get_share_link(pool.as_ref(), &token)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+        let author_ulid = binary_to_ulid(link.author_id.as_slice()).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        if let Some(todo_id) = link.todo_id.as_deref() {
+            let task_ulid = binary_to_ulid(todo_id).map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+            let task = model::tasks::get_task(pool.as_ref(), task_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?
+                .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+            // the author may have lost access to the task since
+            let role = model::shares::get_task_role(pool.as_ref(), author_ulid, &task)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+            if role.is_none() {
+                return Err(HttpResponse::NotFound().body("Not Found"));
+            }
+
+            let mut task = to_task_response(pool.as_ref(), author_ulid, task)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+            task.redact_users();
+
+            return Ok(HttpResponse::Ok().json(task));
+        }
+
+        let view_id = link
+            .view_id
+            .as_deref()
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+        let view_ulid = binary_to_ulid(view_id).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+        let view = get_own_view(pool.as_ref(), author_ulid, view_ulid, false).await?;
+
+        let mut tasks = list_view_tasks(pool.as_ref(), author_ulid, &view, &query).await?;
+        for task in tasks.items.iter_mut() {
+            task.redact_users();
+        }
+
+        Ok(HttpResponse::Ok().json(tasks))
+    }
+
+    get_shared_inner(token, query, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
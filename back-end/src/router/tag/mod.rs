@@ -0,0 +1,339 @@
+use actix_session::Session;
+use actix_web::{
+    delete, dev::HttpServiceFactory, get, patch, post, web, HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, MySql};
+
+use crate::{
+    model::{
+        self,
+        types::{Tag, TagReq, WorkspaceRole},
+        Update,
+    },
+    router::workspace::get_permitted_workspace,
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+pub fn tags_router() -> impl HttpServiceFactory {
+    web::scope("/tags")
+        .service(post_tag)
+        .service(get_tags_me)
+        .service(patch_tag)
+        .service(delete_tag)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagResponse {
+    pub id: String,
+    /// personal when `None`
+    pub workspace_id: Option<String>,
+    pub name: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+impl TryFrom<Tag> for TagResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Tag) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let workspace_id = value
+            .workspace_id
+            .as_deref()
+            .map(binary_to_ulid)
+            .transpose()?
+            .map(|id| id.to_string());
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            workspace_id,
+            name: value.name,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+/// workspace of the tag, which the user changes as its author or as an admin of the workspace
+async fn check_tag_manager(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    tag: &Tag,
+) -> Result<Option<ulid::Ulid>, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let workspace_ulid = tag
+        .workspace_id
+        .as_deref()
+        .map(binary_to_ulid)
+        .transpose()
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    if tag.author_id != ulid_to_binary(user_ulid).to_vec() {
+        let Some(workspace_ulid) = workspace_ulid else {
+            return Err(HttpResponse::Forbidden().body("Forbidden"));
+        };
+        get_permitted_workspace(
+            &mut *conn,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Admin,
+            false,
+        )
+        .await?;
+    }
+
+    Ok(workspace_ulid)
+}
+
+/// personal tags of the user and the tags of their workspaces
+#[get("/me")]
+pub async fn get_tags_me(session: Session, pool: web::Data<sqlx::MySqlPool>) -> impl Responder {
+    async fn get_tags_me_inner(
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let tags = model::tags::get_tags(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(TagResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(tags))
+    }
+
+    get_tags_me_inner(session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostTagRequest {
+    pub name: String,
+    /// creates the tag in the workspace instead of the personal tags
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+}
+#[post("")]
+pub async fn post_tag(
+    _req: HttpRequest,
+    body: web::Json<PostTagRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_tag_inner(
+        _req: HttpRequest,
+        body: web::Json<PostTagRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let name = body.name.trim().to_string();
+        if name.is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid tag name"));
+        }
+
+        let workspace_ulid = body
+            .workspace_id
+            .as_deref()
+            .map(ulid::Ulid::from_string)
+            .transpose()
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+        if let Some(workspace_ulid) = workspace_ulid {
+            get_permitted_workspace(
+                &mut tx,
+                user_ulid,
+                workspace_ulid,
+                WorkspaceRole::Member,
+                false,
+            )
+            .await?;
+        }
+
+        let is_exists = model::tags::is_tag_name_exists(&mut tx, user_ulid, workspace_ulid, &name)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        if is_exists {
+            return Err(HttpResponse::BadRequest().body("Tag already exists"));
+        }
+
+        let tag_ulid = ulid::Ulid::new();
+
+        model::tags::insert_tag(
+            &mut tx,
+            TagReq {
+                id: ulid_to_binary(tag_ulid).to_vec(),
+                author_id: ulid_to_binary(user_ulid).to_vec(),
+                workspace_id: workspace_ulid.map(|id| ulid_to_binary(id).to_vec()),
+                name,
+            },
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().finish())
+    }
+
+    post_tag_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatchTagRequest {
+    #[serde(default)]
+    pub name: Update<String>,
+}
+#[patch("/{id}")]
+pub async fn patch_tag(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PatchTagRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn patch_tag_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PatchTagRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let tag_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid tag id: {}", e)))?;
+
+        let tag = model::tags::get_tag_with_lock(&mut tx, tag_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+        let workspace_ulid = check_tag_manager(&mut tx, user_ulid, &tag).await?;
+
+        let name = body.name.clone().map(|name| name.trim().to_string());
+        if let Update::Set(name) = &name {
+            if name.is_empty() {
+                return Err(HttpResponse::BadRequest().body("Invalid tag name"));
+            }
+            if name != &tag.name {
+                let is_exists =
+                    model::tags::is_tag_name_exists(&mut tx, user_ulid, workspace_ulid, name)
+                        .await
+                        .map_err(|e| {
+                            HttpResponse::InternalServerError()
+                                .body(format!("Internal Server Error: {}", e))
+                        })?;
+                if is_exists {
+                    return Err(HttpResponse::BadRequest().body("Tag already exists"));
+                }
+            }
+        }
+
+        model::tags::update_tag(&mut tx, tag_ulid, model::tags::UpdateTag { name })
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    patch_tag_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[delete("/{id}")]
+pub async fn delete_tag(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_tag_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let tag_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid tag id: {}", e)))?;
+
+        let tag = model::tags::get_tag_with_lock(&mut tx, tag_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+        check_tag_manager(&mut tx, user_ulid, &tag).await?;
+
+        model::tags::delete_tag(&mut tx, tag_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_tag_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
@@ -0,0 +1,139 @@
+use actix_session::Session;
+use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use super::get_permitted_task;
+use crate::{
+    model::{self, types::ShareRole},
+    utils::check_is_logged_in,
+};
+
+#[post("/{id}/archive")]
+pub async fn post_task_archive(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    set_archived(id, session, pool, true)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[post("/{id}/unarchive")]
+pub async fn post_task_unarchive(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    set_archived(id, session, pool, false)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// archives the task, or takes it out of the archive with `false`
+async fn set_archived(
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+    archived: bool,
+) -> Result<HttpResponse, HttpResponse> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let user_ulid = check_is_logged_in(session, &mut tx)
+        .await
+        .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+    let task_ulid = ulid::Ulid::from_string(&id)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+    get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+
+    model::tasks::set_archived(&mut tx, task_ulid, archived)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    tx.commit().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoArchiveRule {
+    /// done tasks untouched for this many days are archived, never when `null`
+    pub days: Option<i32>,
+}
+
+#[get("/auto-archive")]
+pub async fn get_auto_archive(
+    _req: HttpRequest,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_auto_archive_inner(
+        _req: HttpRequest,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let user = model::users::get_user(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+        Ok(HttpResponse::Ok().json(AutoArchiveRule {
+            days: user.auto_archive_days,
+        }))
+    }
+
+    get_auto_archive_inner(_req, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[put("/auto-archive")]
+pub async fn put_auto_archive(
+    _req: HttpRequest,
+    body: web::Json<AutoArchiveRule>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_auto_archive_inner(
+        _req: HttpRequest,
+        body: web::Json<AutoArchiveRule>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        if matches!(body.days, Some(days) if days < 0) {
+            return Err(HttpResponse::BadRequest().body("Invalid days"));
+        }
+
+        model::users::set_auto_archive_days(pool.as_ref(), user_ulid, body.days)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_auto_archive_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use actix_session::Session;
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, MySql};
+
+use super::{
+    check_task_tag_owner, get_permitted_task, trash_permitted_task, update_permitted_task,
+    PutTaskRequest, SubtaskDeletion,
+};
+use crate::{
+    model::{self, types::ShareRole},
+    utils::check_is_logged_in,
+};
+
+/// what happens to the other tasks when one of them fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BulkMode {
+    /// nothing is changed unless every task succeeds
+    #[default]
+    AllOrNothing,
+    /// the tasks which succeed are changed anyway
+    BestEffort,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum BulkAction {
+    /// same fields as `PATCH /tasks/{id}`
+    Update {
+        patch: PutTaskRequest,
+    },
+    /// moves the tasks to the trash
+    Delete {
+        #[serde(default)]
+        subtasks: SubtaskDeletion,
+    },
+    Tag {
+        tag_id: String,
+    },
+    Untag {
+        tag_id: String,
+    },
+    Archive,
+    Unarchive,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostBulkRequest {
+    pub ids: Vec<String>,
+    #[serde(flatten)]
+    pub action: BulkAction,
+    #[serde(default)]
+    pub mode: BulkMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkItemResponse {
+    pub id: String,
+    /// status the task would have got from its own request
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkResponse {
+    /// `false` when an all-or-nothing request was rolled back
+    pub committed: bool,
+    pub results: Vec<BulkItemResponse>,
+}
+
+const MAX_BULK_SIZE: usize = 500;
+
+/// applies the action to one task, returning the tasks it moved to the trash
+async fn apply_action(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+    action: &BulkAction,
+    tag_ulid: Option<ulid::Ulid>,
+) -> Result<Vec<ulid::Ulid>, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    match (action, tag_ulid) {
+        (BulkAction::Update { patch }, _) => {
+            update_permitted_task(&mut *conn, user_ulid, task_ulid, patch, None).await?;
+        }
+        (BulkAction::Delete { subtasks }, _) => {
+            return trash_permitted_task(&mut *conn, user_ulid, task_ulid, *subtasks, None).await;
+        }
+        (BulkAction::Tag { .. }, Some(tag_ulid)) => {
+            check_task_tag_owner(&mut *conn, user_ulid, task_ulid, tag_ulid).await?;
+            model::tags::attach_tag(&mut *conn, task_ulid, tag_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+        (BulkAction::Untag { .. }, Some(tag_ulid)) => {
+            check_task_tag_owner(&mut *conn, user_ulid, task_ulid, tag_ulid).await?;
+            model::tags::detach_tag(&mut *conn, task_ulid, tag_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+        (BulkAction::Archive | BulkAction::Unarchive, _) => {
+            get_permitted_task(&mut *conn, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+            let archived = matches!(action, BulkAction::Archive);
+            model::tasks::set_archived(&mut *conn, task_ulid, archived)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+        (BulkAction::Tag { .. } | BulkAction::Untag { .. }, None) => {
+            return Err(HttpResponse::BadRequest().body("Invalid tag id"));
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// applies one action to many tasks in a single transaction, each task in its own savepoint
+///
+/// ids listed more than once are applied once, and deleting a task already trashed along with
+/// its parent earlier in the request succeeds
+#[post("/bulk")]
+pub async fn post_tasks_bulk(
+    _req: HttpRequest,
+    body: web::Json<PostBulkRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_tasks_bulk_inner(
+        _req: HttpRequest,
+        body: web::Json<PostBulkRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        if body.ids.is_empty() || body.ids.len() > MAX_BULK_SIZE {
+            return Err(HttpResponse::BadRequest()
+                .body(format!("Between 1 and {} ids are required", MAX_BULK_SIZE)));
+        }
+        let tag_ulid = match &body.action {
+            BulkAction::Tag { tag_id } | BulkAction::Untag { tag_id } => {
+                Some(ulid::Ulid::from_string(tag_id).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid tag id: {}", e))
+                })?)
+            }
+            _ => None,
+        };
+
+        let mut seen = HashSet::new();
+        let ids = body.ids.iter().filter(|id| {
+            let key =
+                ulid::Ulid::from_string(id).map_or_else(|_| id.to_string(), |u| u.to_string());
+            seen.insert(key)
+        });
+
+        let mut trashed = HashSet::new();
+        let mut results = Vec::with_capacity(body.ids.len());
+        for id in ids {
+            let result = match ulid::Ulid::from_string(id) {
+                Ok(task_ulid) if trashed.contains(&task_ulid) => Ok(()),
+                Ok(task_ulid) => {
+                    let mut savepoint = tx.begin().await.map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+                    let result =
+                        apply_action(&mut savepoint, user_ulid, task_ulid, &body.action, tag_ulid)
+                            .await;
+                    if result.is_ok() {
+                        savepoint.commit().await
+                    } else {
+                        savepoint.rollback().await
+                    }
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+                    result.map(|trashed_now| trashed.extend(trashed_now))
+                }
+                Err(e) => Err(HttpResponse::BadRequest().body(format!("Invalid task id: {}", e))),
+            };
+
+            let item = match result {
+                Ok(()) => BulkItemResponse {
+                    id: id.clone(),
+                    status: 204,
+                    error: None,
+                },
+                Err(response) => {
+                    let status = response.status().as_u16();
+                    let error = actix_web::body::to_bytes(response.into_body())
+                        .await
+                        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                        .unwrap_or_default();
+                    BulkItemResponse {
+                        id: id.clone(),
+                        status,
+                        error: Some(error),
+                    }
+                }
+            };
+            results.push(item);
+        }
+
+        let is_failed = results.iter().any(|result| result.error.is_some());
+        let committed = !(is_failed && body.mode == BulkMode::AllOrNothing);
+        if committed {
+            tx.commit().await
+        } else {
+            tx.rollback().await
+        }
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Ok().json(BulkResponse { committed, results }))
+    }
+
+    post_tasks_bulk_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
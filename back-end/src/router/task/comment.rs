@@ -0,0 +1,305 @@
+use actix_session::Session;
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use super::get_permitted_task;
+use crate::{
+    model::{
+        self,
+        types::{ShareRole, TaskComment, TaskCommentReq, VecWithTotal},
+    },
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentResponse {
+    pub id: String,
+    pub task_id: String,
+    pub author_id: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+impl TryFrom<TaskComment> for CommentResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TaskComment) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let task_id = binary_to_ulid(value.todo_id.as_slice())?;
+        let author_id = binary_to_ulid(value.author_id.as_slice())?;
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            task_id: task_id.to_string(),
+            author_id: author_id.to_string(),
+            body: value.body,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+/// the comment of the task, `Forbidden` unless it was written by the user
+async fn get_own_comment(
+    conn: impl sqlx::Acquire<'_, Database = sqlx::MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+    comment_ulid: ulid::Ulid,
+) -> Result<TaskComment, HttpResponse> {
+    let comment = model::comments::get_comment(conn, comment_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    if comment.todo_id != ulid_to_binary(task_ulid).to_vec() {
+        return Err(HttpResponse::NotFound().body("Not Found"));
+    }
+    if comment.author_id != ulid_to_binary(user_ulid).to_vec() {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(comment)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetCommentQuery {
+    /// defaults to 50
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+#[get("/{id}/comments")]
+pub async fn get_task_comments(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    query: web::Query<GetCommentQuery>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_task_comments_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        query: web::Query<GetCommentQuery>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        get_permitted_task(
+            pool.as_ref(),
+            user_ulid,
+            task_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let comments = model::comments::get_comments_of_task(
+            pool.as_ref(),
+            task_ulid,
+            query.limit.unwrap_or(50),
+            query.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let items = comments
+            .items
+            .into_iter()
+            .map(CommentResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(VecWithTotal {
+            total: comments.total,
+            items,
+            tag_counts: None,
+            next_cursor: None,
+            prev_cursor: None,
+        }))
+    }
+
+    get_task_comments_inner(_req, id, query, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostCommentRequest {
+    pub body: String,
+}
+#[post("/{id}/comments")]
+pub async fn post_task_comment(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PostCommentRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_task_comment_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PostCommentRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Viewer, true).await?;
+
+        if body.body.trim().is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid comment body"));
+        }
+
+        let comment_ulid = ulid::Ulid::new();
+
+        model::comments::insert_comment(
+            &mut tx,
+            TaskCommentReq {
+                id: ulid_to_binary(comment_ulid).to_vec(),
+                todo_id: ulid_to_binary(task_ulid).to_vec(),
+                author_id: ulid_to_binary(user_ulid).to_vec(),
+                body: body.body.clone(),
+            },
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().finish())
+    }
+
+    post_task_comment_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchCommentRequest {
+    pub body: String,
+}
+#[patch("/{id}/comments/{comment_id}")]
+pub async fn patch_task_comment(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    body: web::Json<PatchCommentRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn patch_task_comment_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        body: web::Json<PatchCommentRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&path.0)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+        let comment_ulid = ulid::Ulid::from_string(&path.1)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid comment id: {}", e)))?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Viewer, true).await?;
+        get_own_comment(&mut tx, user_ulid, task_ulid, comment_ulid).await?;
+
+        if body.body.trim().is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid comment body"));
+        }
+
+        model::comments::update_comment(&mut tx, comment_ulid, &body.body)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    patch_task_comment_inner(_req, path, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[delete("/{id}/comments/{comment_id}")]
+pub async fn delete_task_comment(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_task_comment_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&path.0)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+        let comment_ulid = ulid::Ulid::from_string(&path.1)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid comment id: {}", e)))?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Viewer, true).await?;
+        get_own_comment(&mut tx, user_ulid, task_ulid, comment_ulid).await?;
+
+        model::comments::delete_comment(&mut tx, comment_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_task_comment_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
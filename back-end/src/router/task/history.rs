@@ -0,0 +1,110 @@
+use actix_session::Session;
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{
+        self,
+        types::{ShareRole, TaskEvent, TaskEventAction},
+    },
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEventResponse {
+    pub id: String,
+    pub task_id: String,
+    /// `None` once the user has been deleted
+    pub actor_id: Option<String>,
+    pub action: TaskEventAction,
+    /// `{ "before": ..., "after": ... }` for each changed field
+    pub changes: serde_json::Value,
+    pub created_at: String,
+}
+impl TryFrom<TaskEvent> for TaskEventResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TaskEvent) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let task_id = binary_to_ulid(value.todo_id.as_slice())?;
+        let actor_id = value
+            .actor_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let changes = serde_json::from_str(&value.changes)?;
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            task_id: task_id.to_string(),
+            actor_id: actor_id.map(|id| id.to_string()),
+            action: value.action,
+            changes,
+            created_at,
+        })
+    }
+}
+
+/// the history stays readable by the author of the task after it is deleted
+#[get("/{id}/history")]
+pub async fn get_task_history(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_task_history_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let task = model::tasks::get_task(pool.as_ref(), task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        if let Some(task) = &task {
+            let role = model::shares::get_task_role(pool.as_ref(), user_ulid, task)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+            if role < Some(ShareRole::Viewer) {
+                return Err(HttpResponse::Forbidden().body("Forbidden"));
+            }
+        }
+
+        let events = model::history::get_task_events(pool.as_ref(), task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        let bin_user_id = Some(ulid_to_binary(user_ulid).to_vec());
+        if task.is_none() && events.last().map(|e| &e.author_id) != Some(&bin_user_id) {
+            return Err(HttpResponse::NotFound().body("Not Found"));
+        }
+
+        let events = events
+            .into_iter()
+            .map(TaskEventResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(events))
+    }
+
+    get_task_history_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
@@ -1,15 +1,41 @@
-use std::{collections::HashSet, str::FromStr};
+mod archive;
+mod bulk;
+mod comment;
+mod history;
+mod reminder;
+pub mod share;
+mod trash;
+pub mod view;
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    str::FromStr,
+};
 
 use actix_session::Session;
 use actix_web::{
-    delete, dev::HttpServiceFactory, get, patch, post, web, HttpRequest, HttpResponse, Responder,
+    delete,
+    dev::HttpServiceFactory,
+    get,
+    http::{
+        header::{ContentType, ETag, EntityTag, IfMatch},
+        StatusCode,
+    },
+    patch, post, put, web, HttpMessage, HttpRequest, HttpResponse, Responder,
 };
 use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, MySql};
 
+use super::{project::get_permitted_project, tag::TagResponse, workspace::get_permitted_workspace};
 use crate::{
     model::{
         self,
-        types::{TaskPriority, TaskState, Todo, TodoReq, VecWithTotal},
+        tasks::SearchMatch,
+        types::{
+            LinkedTask, NotificationKind, Recurrence, ShareRole, Tag, TaskPriority, TaskState,
+            Todo, TodoReq, VecWithTotal, WorkspaceRole,
+        },
         Update,
     },
     utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
@@ -19,65 +45,582 @@ pub fn tasks_router() -> impl HttpServiceFactory {
     web::scope("/tasks")
         .service(post_task)
         .service(get_tasks_me)
+        .service(get_tasks_assigned)
+        .service(bulk::post_tasks_bulk)
+        .service(view::views_router())
+        .service(trash::get_trash)
+        .service(trash::delete_trash)
+        .service(trash::delete_trashed_task)
+        .service(trash::post_task_restore)
+        .service(archive::get_auto_archive)
+        .service(archive::put_auto_archive)
+        .service(archive::post_task_archive)
+        .service(archive::post_task_unarchive)
         .service(get_task)
         .service(delete_task)
         .service(patch_task)
+        .service(put_task_tag)
+        .service(delete_task_tag)
+        .service(post_subtask)
+        .service(put_task_parent)
+        .service(put_task_project)
+        .service(get_task_subtree)
+        .service(put_task_blocker)
+        .service(delete_task_blocker)
+        .service(reminder::get_task_reminders)
+        .service(reminder::post_task_reminder)
+        .service(reminder::delete_task_reminder)
+        .service(comment::get_task_comments)
+        .service(comment::post_task_comment)
+        .service(comment::patch_task_comment)
+        .service(comment::delete_task_comment)
+        .service(history::get_task_history)
+        .service(share::get_task_shares)
+        .service(share::put_task_share)
+        .service(share::delete_task_share)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResponse {
     pub id: String,
+    /// left out on public share links
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub author_id: String,
+    /// the one doing the task, who may differ from its author
+    pub assignee_id: Option<String>,
     pub title: String,
     pub description: String,
     pub created_at: String,
     pub updated_at: String,
+    /// same as the `ETag` header of `GET /tasks/{id}`, to be sent back as `If-Match`
+    pub etag: String,
 
     pub state: TaskState,
     pub priority: Option<TaskPriority>,
     pub due_date: Option<String>,
+    pub recurrence: Option<Recurrence>,
+    /// hidden from the task list by default since then
+    pub archived_at: Option<String>,
+
+    pub tags: Vec<TagResponse>,
+
+    pub parent_id: Option<String>,
+    /// in the inbox when `None`
+    pub project_id: Option<String>,
+    /// personal when `None`
+    pub workspace_id: Option<String>,
+    /// direct subtasks
+    pub child_count: usize,
+    pub done_child_count: usize,
+    /// `done_child_count / child_count`, absent without subtasks
+    pub progress: Option<f64>,
+
+    /// tasks this task is blocked by
+    pub blockers: Vec<LinkedTaskResponse>,
+    /// tasks blocked by this task
+    pub dependents: Vec<LinkedTaskResponse>,
+
+    /// only for tasks in the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<SearchMatch>,
 }
-impl TryFrom<Todo> for TaskResponse {
+impl TryFrom<(Todo, Vec<Tag>)> for TaskResponse {
     type Error = anyhow::Error;
 
-    fn try_from(value: Todo) -> Result<Self, Self::Error> {
+    fn try_from((value, tags): (Todo, Vec<Tag>)) -> Result<Self, Self::Error> {
         let id = binary_to_ulid(value.id.as_slice())?;
+        let etag = task_etag(&value).to_string();
         let author_id_content = value
             .author_id
             .ok_or_else(|| anyhow::anyhow!("Invalid author_id"))?;
         let author_id = binary_to_ulid(author_id_content.as_slice())?;
+        let assignee_id = value
+            .assignee_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let parent_id = value
+            .parent_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let project_id = value
+            .project_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
+        let workspace_id = value
+            .workspace_id
+            .map(|id| binary_to_ulid(id.as_slice()))
+            .transpose()?;
         let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
         let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
         let due_date = value
             .due_date
             .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let archived_at = value
+            .archived_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let deleted_at = value
+            .deleted_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let tags = tags
+            .into_iter()
+            .map(TagResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self {
             id: id.to_string(),
             author_id: author_id.to_string(),
+            assignee_id: assignee_id.map(|id| id.to_string()),
             title: value.title,
             description: value.description,
             created_at,
             updated_at,
+            etag,
 
             state: value.state,
             priority: value.priority,
             due_date,
+            recurrence: value.recurrence,
+            archived_at,
+
+            tags,
+
+            parent_id: parent_id.map(|id| id.to_string()),
+            project_id: project_id.map(|id| id.to_string()),
+            workspace_id: workspace_id.map(|id| id.to_string()),
+            child_count: 0,
+            done_child_count: 0,
+            progress: None,
+
+            blockers: Vec::new(),
+            dependents: Vec::new(),
+
+            deleted_at,
+
+            search: None,
         })
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl TaskResponse {
+    /// hides the users behind the task from those without an account
+    pub(crate) fn redact_users(&mut self) {
+        self.author_id = String::new();
+        self.assignee_id = None;
+    }
+}
+
+/// changes with every update of the task
+fn task_etag(task: &Todo) -> EntityTag {
+    EntityTag::new_strong(task.version.to_string())
+}
+
+/// fails with 412 unless `If-Match` is absent, `*` or matches the current `ETag` of the task
+#[allow(clippy::result_large_err)]
+fn check_if_match(if_match: Option<&IfMatch>, task: &Todo) -> Result<(), HttpResponse> {
+    let etag = task_etag(task);
+    match if_match {
+        None | Some(IfMatch::Any) => Ok(()),
+        Some(IfMatch::Items(items)) if items.iter().any(|item| item.strong_eq(&etag)) => Ok(()),
+        Some(IfMatch::Items(_)) => Err(HttpResponse::PreconditionFailed()
+            .insert_header(ETag(etag))
+            .body("Precondition Failed")),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedTaskResponse {
+    pub id: String,
+    pub title: String,
+    pub state: TaskState,
+}
+impl TryFrom<LinkedTask> for LinkedTaskResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: LinkedTask) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+
+        Ok(Self {
+            id: id.to_string(),
+            title: value.title,
+            state: value.state,
+        })
+    }
+}
+
+/// body of the 409 refusing to start or finish a task whose blockers are not done yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnfinishedBlockersResponse {
+    pub blockers: Vec<LinkedTaskResponse>,
+    /// unfinished blockers left out of `blockers` because the user cannot see them
+    pub hidden_blockers: usize,
+}
+
+/// `user_ulid` is the viewer, whose access decides which linked tasks are listed
+async fn to_task_responses(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    tasks: Vec<Todo>,
+) -> anyhow::Result<Vec<TaskResponse>> {
+    let mut conn = conn.acquire().await?;
+
+    let task_ulids = tasks
+        .iter()
+        .map(|task| binary_to_ulid(task.id.as_slice()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut tags = model::tags::get_tags_of_tasks(&mut *conn, &task_ulids).await?;
+    let child_counts = model::tasks::get_child_counts(&mut *conn, user_ulid, &task_ulids).await?;
+    let (mut blockers, mut dependents) =
+        model::dependencies::get_dependencies_of_tasks(&mut *conn, user_ulid, &task_ulids).await?;
+
+    tasks
+        .into_iter()
+        .zip(task_ulids)
+        .map(|(task, task_ulid)| {
+            let task_tags = tags.remove(&task_ulid).unwrap_or_default();
+            let mut response = TaskResponse::try_from((task, task_tags))?;
+
+            let child_count = child_counts.get(&task_ulid).copied().unwrap_or_default();
+            response.child_count = child_count.total;
+            response.done_child_count = child_count.done;
+            if child_count.total > 0 {
+                response.progress = Some(child_count.done as f64 / child_count.total as f64);
+            }
+
+            response.blockers = blockers
+                .remove(&task_ulid)
+                .unwrap_or_default()
+                .into_iter()
+                .map(LinkedTaskResponse::try_from)
+                .collect::<Result<_, _>>()?;
+            response.dependents = dependents
+                .remove(&task_ulid)
+                .unwrap_or_default()
+                .into_iter()
+                .map(LinkedTaskResponse::try_from)
+                .collect::<Result<_, _>>()?;
+
+            Ok(response)
+        })
+        .collect()
+}
+
+pub(crate) async fn to_task_response(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task: Todo,
+) -> anyhow::Result<TaskResponse> {
+    to_task_responses(conn, user_ulid, vec![task])
+        .await?
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("Task not found"))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GetTaskQuery {
     phrase: Option<String>,
+    /// full-text search, e.g. `release "weekly report" -draft`, sorted by relevance by default
+    search: Option<String>,
+    /// structured query such as
+    /// `state:todo,in-progress priority:high due:<2026-11-01 tag:work "release notes"`,
+    /// combined with the other filters
+    q: Option<String>,
 
     limit: Option<usize>,
     offset: Option<usize>,
+    /// `next_cursor` / `prev_cursor` of a previous response, used with `limit`
+    cursor: Option<String>,
+    /// count all the tasks matching the filter, defaults to `true`
+    count_total: Option<bool>,
+
+    /// `created_at`, `updated_at`, `priority`, `priority,created_at`, `priority,updated_at`,
+    /// `due_date`, `title` or `state`
+    sort: Option<String>,
+    /// `asc` or `desc` for each key of `sort`
+    order: Option<String>,
 
     state: Option<TaskState>,
     priority: Option<TaskPriority>,
+    /// `[state, ...]`
     state_filter: Option<String>,
+    /// `[priority, ...]`, where `none` matches tasks without priority
+    priority_filter: Option<String>,
+
+    /// `%Y-%m-%d %H:%M:%S` or `%Y-%m-%d`, exclusive
+    due_before: Option<String>,
+    due_after: Option<String>,
+    overdue: Option<bool>,
+    created_before: Option<String>,
+    created_after: Option<String>,
+    updated_before: Option<String>,
+    updated_after: Option<String>,
+
+    /// `[tag_id, ...]`: tasks tagged with any of them
+    tags: Option<String>,
+    /// `[tag_id, ...]`: tasks tagged with all of them
+    all_tags: Option<String>,
+    /// include per-tag counts of the filtered tasks in the response
+    tag_counts: Option<bool>,
+
+    /// project id, or `inbox` for the tasks without a project
+    project: Option<String>,
+    /// workspace id, or `personal` for the tasks outside of any workspace
+    workspace: Option<String>,
+    /// user id of the assignee
+    assignee: Option<String>,
+
+    /// list archived tasks along with the others, defaults to `false`
+    include_archived: Option<bool>,
+    /// list archived tasks only, defaults to `false`
+    archived_only: Option<bool>,
+}
+#[allow(clippy::result_large_err)]
+impl GetTaskQuery {
+    fn to_limit(
+        &self,
+        sorted_by: &model::tasks::SortedBy,
+    ) -> Result<Option<model::tasks::Limit>, HttpResponse> {
+        match (self.limit, self.offset, &self.cursor) {
+            (Some(limit), Some(offset), None) => {
+                Ok(Some(model::tasks::Limit::LimitOffset(limit, offset)))
+            }
+            (Some(limit), None, None) => Ok(Some(model::tasks::Limit::Limit(limit))),
+            (Some(limit), None, Some(cursor)) => {
+                let cursor = model::tasks::Cursor::decode(cursor, sorted_by).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid cursor: {}", e))
+                })?;
+                Ok(Some(model::tasks::Limit::Cursor(limit, cursor)))
+            }
+            (None, None, None) => Ok(None),
+            _ => Err(HttpResponse::BadRequest().body("Invalid query")),
+        }
+    }
+
+    fn to_sorted_by(&self) -> Result<Option<model::tasks::SortedBy>, HttpResponse> {
+        let sorted_by = match (&self.sort, &self.order) {
+            (Some(sort), order) => Some(
+                model::tasks::SortedBy::from_query(sort, order.as_deref()).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid query: {}", e))
+                })?,
+            ),
+            (None, Some(_)) => return Err(HttpResponse::BadRequest().body("Invalid query")),
+            (None, None) => None,
+        };
+
+        match (sorted_by, &self.search) {
+            (Some(model::tasks::SortedBy::Relevance(_)), None) => {
+                Err(HttpResponse::BadRequest().body("Invalid query: relevance needs search"))
+            }
+            (None, Some(_)) => Ok(Some(model::tasks::SortedBy::Relevance(
+                model::tasks::Order::Desc,
+            ))),
+            (sorted_by, _) => Ok(sorted_by),
+        }
+    }
+
+    fn to_filter(&self) -> Result<model::tasks::TaskFilter, HttpResponse> {
+        let state = intersect_filter(
+            self.state_filter
+                .as_deref()
+                .map(parse_list_query)
+                .transpose()?,
+            self.state,
+        );
+        let priority = intersect_filter(
+            self.priority_filter
+                .as_deref()
+                .map(parse_list_query::<PriorityFilter>)
+                .transpose()?
+                .map(|p| p.into_iter().map(|p| p.0).collect()),
+            self.priority.map(Some),
+        );
+
+        Ok(model::tasks::TaskFilter {
+            phrase: self.phrase.clone(),
+            search: self
+                .search
+                .as_deref()
+                .map(model::tasks::SearchQuery::parse)
+                .transpose()
+                .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid search: {}", e)))?,
+            state,
+            priority,
+            due_before: parse_datetime_query(&self.due_before)?,
+            due_after: parse_datetime_query(&self.due_after)?,
+            overdue: self.overdue,
+            created_before: parse_datetime_query(&self.created_before)?,
+            created_after: parse_datetime_query(&self.created_after)?,
+            updated_before: parse_datetime_query(&self.updated_before)?,
+            updated_after: parse_datetime_query(&self.updated_after)?,
+            tags: self.tags.as_deref().map(parse_list_query).transpose()?,
+            all_tags: self.all_tags.as_deref().map(parse_list_query).transpose()?,
+            project: self
+                .project
+                .as_deref()
+                .map(|project| match project {
+                    "inbox" => Ok(model::tasks::ProjectFilter::Inbox),
+                    project => ulid::Ulid::from_string(project)
+                        .map(model::tasks::ProjectFilter::Project)
+                        .map_err(|e| {
+                            HttpResponse::BadRequest().body(format!("Invalid project id: {}", e))
+                        }),
+                })
+                .transpose()?,
+            workspace: self
+                .workspace
+                .as_deref()
+                .map(|workspace| match workspace {
+                    "personal" => Ok(model::tasks::WorkspaceFilter::Personal),
+                    workspace => ulid::Ulid::from_string(workspace)
+                        .map(model::tasks::WorkspaceFilter::Workspace)
+                        .map_err(|e| {
+                            HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e))
+                        }),
+                })
+                .transpose()?,
+            assignee: self
+                .assignee
+                .as_deref()
+                .map(ulid::Ulid::from_string)
+                .transpose()
+                .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid assignee: {}", e)))?,
+            expr: self
+                .q
+                .as_deref()
+                .map(model::tasks::FilterExpr::from_str)
+                .transpose()
+                .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid q: {}", e)))?,
+            archived: match (self.archived_only, self.include_archived) {
+                (Some(true), _) => model::tasks::ArchivedFilter::Only,
+                (_, Some(true)) => model::tasks::ArchivedFilter::Include,
+                _ => model::tasks::ArchivedFilter::Exclude,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PriorityFilter(Option<TaskPriority>);
+impl FromStr for PriorityFilter {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(PriorityFilter(None)),
+            s => TaskPriority::from_str(s).map(|p| PriorityFilter(Some(p))),
+        }
+    }
+}
+
+/// narrows a list filter down to a single value when both are given
+fn intersect_filter<T: PartialEq>(list: Option<Vec<T>>, single: Option<T>) -> Option<Vec<T>> {
+    match (list, single) {
+        (Some(list), Some(single)) => Some(list.into_iter().filter(|v| *v == single).collect()),
+        (Some(list), None) => Some(list),
+        (None, Some(single)) => Some(vec![single]),
+        (None, None) => None,
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_datetime_query(s: &Option<String>) -> Result<Option<chrono::NaiveDateTime>, HttpResponse> {
+    s.as_deref()
+        .map(|s| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .or_else(|_| {
+                    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_hms(0, 0, 0))
+                })
+                .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid date: {}", e)))
+        })
+        .transpose()
+}
+
+/// parses a bracketed list query such as `[todo, done]`
+#[allow(clippy::result_large_err)]
+fn parse_list_query<T: FromStr + Eq + Hash>(s: &str) -> Result<Vec<T>, HttpResponse> {
+    s.strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .map(|s| {
+            s.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    T::from_str(s).map_err(|_e| HttpResponse::BadRequest().body("Invalid query"))
+                })
+                .collect::<Result<HashSet<T>, HttpResponse>>()
+                .map(|s| s.into_iter().collect::<Vec<_>>())
+        })
+        .ok_or_else(|| HttpResponse::BadRequest().body("Invalid query"))
+        .and_then(std::convert::identity)
+}
+/// lists the tasks of `user_ulid` matching `query`, shared by `/tasks/me` and saved views
+async fn list_tasks(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    query: &GetTaskQuery,
+) -> Result<VecWithTotal<TaskResponse>, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let sorted_by = query.to_sorted_by()?.unwrap_or_default();
+    let limit = query.to_limit(&sorted_by)?;
+    let filter = query.to_filter()?;
+
+    let tasks = model::tasks::get_tasks(
+        &mut *conn,
+        user_ulid,
+        &filter,
+        limit,
+        Some(sorted_by),
+        query.count_total.unwrap_or(true),
+    )
+    .await
+    .map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+    let tag_counts = if query.tag_counts.unwrap_or(false) {
+        Some(
+            model::tasks::get_tag_counts(&mut *conn, user_ulid, &filter)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?,
+        )
+    } else {
+        None
+    };
+    let search_matches = filter.search.as_ref().map(|search| {
+        tasks
+            .items
+            .iter()
+            .map(|task| search.to_match(task))
+            .collect::<Vec<_>>()
+    });
+    let mut items = to_task_responses(&mut *conn, user_ulid, tasks.items)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if let Some(search_matches) = search_matches {
+        for (item, search_match) in items.iter_mut().zip(search_matches) {
+            item.search = Some(search_match);
+        }
+    }
+    let tasks = VecWithTotal {
+        total: tasks.total,
+        items,
+        tag_counts,
+        next_cursor: tasks.next_cursor,
+        prev_cursor: tasks.prev_cursor,
+    };
+
+    Ok(tasks)
 }
+
 #[get("/me")]
 pub async fn get_tasks_me(
     session: Session,
@@ -93,120 +636,317 @@ pub async fn get_tasks_me(
             .await
             .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
 
-        let limit = match (query.limit, query.offset) {
-            (Some(limit), Some(offset)) => Some(model::tasks::Limit::LimitOffset(limit, offset)),
-            (Some(limit), None) => Some(model::tasks::Limit::Limit(limit)),
-            (None, Some(_)) => {
-                return Err(HttpResponse::BadRequest().body("Invalid query"));
-            }
-            (None, None) => None,
-        };
-        let state_filter = query
-            .state_filter
-            .clone()
-            .map(|s| {
-                s.strip_prefix('[')
-                    .and_then(|s| s.strip_suffix(']'))
-                    .map(|s| {
-                        s.split(',')
-                            .map(|s| s.trim().to_string())
-                            .map(|s| {
-                                TaskState::from_str(&s)
-                                    .map_err(|_e| HttpResponse::BadRequest().body("Invalid query"))
-                            })
-                            .collect::<Result<HashSet<TaskState>, HttpResponse>>()
-                            .map(|s| s.into_iter().collect::<Vec<_>>())
-                    })
-                    .ok_or_else(|| HttpResponse::BadRequest().body("Invalid query"))
-                    .and_then(std::convert::identity)
-            })
-            .transpose()?;
+        let tasks = list_tasks(pool.as_ref(), user_ulid, &query).await?;
 
-        let tasks = model::tasks::get_tasks(
-            pool.as_ref(),
-            user_ulid,
-            query.phrase.clone(),
-            limit,
-            None,
-            state_filter,
-        )
+        Ok(HttpResponse::Ok().json(tasks))
+    }
+
+    get_tasks_me_inner(session, pool, query)
         .await
-        .map_err(|e| {
-            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
-        })?;
-        let tasks = VecWithTotal {
-            total: tasks.total,
-            items: tasks
-                .items
-                .into_iter()
-                .map(TaskResponse::try_from)
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| {
-                    HttpResponse::InternalServerError()
-                        .body(format!("Internal Server Error: {}", e))
-                })?,
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// tasks assigned to the user, in every project they can access, filtered like `/tasks/me`
+#[get("/assigned")]
+pub async fn get_tasks_assigned(
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+    query: web::Query<GetTaskQuery>,
+) -> impl Responder {
+    async fn get_tasks_assigned_inner(
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+        query: web::Query<GetTaskQuery>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let query = GetTaskQuery {
+            assignee: Some(user_ulid.to_string()),
+            ..query.into_inner()
         };
+        let tasks = list_tasks(pool.as_ref(), user_ulid, &query).await?;
 
         Ok(HttpResponse::Ok().json(tasks))
     }
 
-    get_tasks_me_inner(session, pool, query)
+    get_tasks_assigned_inner(session, pool, query)
         .await
         .unwrap_or_else(std::convert::identity)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostTaskRequest {
+    /// chosen by the client, e.g. for a task created offline, generated when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// in the inbox when absent, subtasks always follow their parent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// user id of the one doing the task, who must have access to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee_id: Option<String>,
+    /// seen by all the members of the workspace, personal when absent, subtasks always follow
+    /// their parent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
     pub title: String,
     pub description: String,
     pub state: TaskState,
     pub priority: Option<TaskPriority>,
     pub due_date: Option<String>,
+    /// e.g. `FREQ=WEEKLY;BYDAY=MO,TH;UNTIL=20261231`, recreated with the next due date when done
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+}
+/// a client-supplied id taken between `to_task_ulid` and the insertion conflicts like one taken
+/// before
+fn insert_task_error(e: anyhow::Error) -> HttpResponse {
+    if model::is_duplicate_key(&e) {
+        HttpResponse::Conflict().body("Task already exists")
+    } else {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    }
+}
+
+impl PostTaskRequest {
+    /// the id chosen by the client, unless it is already taken, or a new one
+    async fn to_task_ulid(
+        &self,
+        conn: impl Acquire<'_, Database = MySql>,
+    ) -> Result<ulid::Ulid, HttpResponse> {
+        let Some(id) = self.id.as_ref() else {
+            return Ok(ulid::Ulid::new());
+        };
+
+        let task_ulid = ulid::Ulid::from_string(id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let exists = model::tasks::task_exists(conn, task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        if exists {
+            return Err(HttpResponse::Conflict().body("Task already exists"));
+        }
+
+        Ok(task_ulid)
+    }
+
+    /// the project chosen by the client, checking that `user_ulid` may edit it
+    async fn to_project_id(
+        &self,
+        conn: impl Acquire<'_, Database = MySql>,
+        user_ulid: ulid::Ulid,
+    ) -> Result<Option<Vec<u8>>, HttpResponse> {
+        let Some(project_id) = self.project_id.as_ref() else {
+            return Ok(None);
+        };
+
+        let project_ulid = ulid::Ulid::from_string(project_id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        let project =
+            get_permitted_project(conn, user_ulid, project_ulid, ShareRole::Editor, false).await?;
+
+        Ok(Some(project.id))
+    }
+
+    /// the workspace chosen by the client, checking that `user_ulid` is a member of it
+    async fn to_workspace_id(
+        &self,
+        conn: impl Acquire<'_, Database = MySql>,
+        user_ulid: ulid::Ulid,
+    ) -> Result<Option<Vec<u8>>, HttpResponse> {
+        let Some(workspace_id) = self.workspace_id.as_ref() else {
+            return Ok(None);
+        };
+
+        let workspace_ulid = ulid::Ulid::from_string(workspace_id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        let workspace = get_permitted_workspace(
+            conn,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Member,
+            false,
+        )
+        .await?;
+
+        Ok(Some(workspace.id))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn to_todo_req(
+        &self,
+        task_ulid: ulid::Ulid,
+        user_ulid: ulid::Ulid,
+        parent_ulid: Option<ulid::Ulid>,
+        project_id: Option<Vec<u8>>,
+        workspace_id: Option<Vec<u8>>,
+    ) -> Result<TodoReq, HttpResponse> {
+        let due_date = self
+            .due_date
+            .as_ref()
+            .map(|d| {
+                chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S").map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid due date: {}", e))
+                })
+            })
+            .transpose()?;
+        let assignee_id = self
+            .assignee_id
+            .as_deref()
+            .map(parse_assignee_id)
+            .transpose()?;
+
+        Ok(TodoReq {
+            id: ulid_to_binary(task_ulid).to_vec(),
+            author_id: Some(ulid_to_binary(user_ulid).to_vec()),
+            assignee_id,
+            parent_id: parent_ulid.map(|id| ulid_to_binary(id).to_vec()),
+            project_id,
+            workspace_id,
+            title: self.title.clone(),
+            description: self.description.clone(),
+            state: self.state,
+            priority: self.priority,
+            due_date,
+            recurrence: self.recurrence.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostTaskResponse {
+    pub id: String,
+}
+
+/// the `Idempotency-Key` header, if any
+#[allow(clippy::result_large_err)]
+fn parse_idempotency_key(req: &HttpRequest) -> Result<Option<&str>, HttpResponse> {
+    req.headers()
+        .get("Idempotency-Key")
+        .map(|key| {
+            key.to_str()
+                .ok()
+                .filter(|key| !key.is_empty() && key.len() <= 255)
+                .ok_or_else(|| HttpResponse::BadRequest().body("Invalid Idempotency-Key"))
+        })
+        .transpose()
 }
 
+/// with an `Idempotency-Key` header, a retry of the same request responds with the result of
+/// the first one instead of creating the task again
 #[post("")]
 pub async fn post_task(
-    _req: HttpRequest,
+    req: HttpRequest,
     body: web::Json<PostTaskRequest>,
     session: Session,
     pool: web::Data<sqlx::MySqlPool>,
 ) -> impl Responder {
     async fn post_task_inner(
-        _req: HttpRequest,
+        req: HttpRequest,
         body: web::Json<PostTaskRequest>,
         session: Session,
         pool: web::Data<sqlx::MySqlPool>,
     ) -> Result<HttpResponse, HttpResponse> {
-        let user_ulid = check_is_logged_in(session, pool.as_ref())
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
             .await
             .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
 
-        let task_ulid = ulid::Ulid::new();
+        let idempotency_key = parse_idempotency_key(&req)?;
+        if let Some(key) = idempotency_key {
+            let request = serde_json::to_string(&*body).map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+            let claimed =
+                model::idempotency::claim_idempotency_key(&mut tx, user_ulid, key, &request)
+                    .await
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+            if let Some(claimed) = claimed {
+                if claimed.request != request {
+                    return Err(HttpResponse::UnprocessableEntity()
+                        .body("Idempotency-Key already used for another request"));
+                }
+                let (Some(status), Some(response)) = (claimed.status, claimed.response) else {
+                    return Err(HttpResponse::Conflict().body("Request in progress"));
+                };
+                let status = StatusCode::from_u16(status).map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
 
-        model::tasks::insert_task(
-            pool.as_ref(),
-            TodoReq {
-                id: ulid_to_binary(task_ulid).to_vec(),
-                author_id: Some(ulid_to_binary(user_ulid).to_vec()),
-                title: body.title.clone(),
-                description: body.description.clone(),
-                state: body.state,
-                priority: body.priority,
-                due_date: body.due_date.as_ref().map(|d| {
-                    chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S")
-                        .expect("Invalid due_date")
-                }),
-            },
-        )
-        .await
-        .map_err(|e| {
+                return Ok(HttpResponse::build(status)
+                    .insert_header(("Idempotent-Replayed", "true"))
+                    .content_type(ContentType::json())
+                    .body(response));
+            }
+        }
+
+        let task_ulid = body.to_task_ulid(&mut tx).await?;
+        let project_id = body.to_project_id(&mut tx, user_ulid).await?;
+        let workspace_id = body.to_workspace_id(&mut tx, user_ulid).await?;
+        let task_req = body.to_todo_req(task_ulid, user_ulid, None, project_id, workspace_id)?;
+
+        model::tasks::insert_task(&mut tx, task_req.clone())
+            .await
+            .map_err(insert_task_error)?;
+        if let Some(assignee_id) = task_req.assignee_id.as_deref() {
+            let task = model::tasks::get_task(&mut tx, task_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?
+                .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+            check_assignee(&mut tx, &task, assignee_id).await?;
+        }
+
+        model::history::record_creation(&mut tx, user_ulid, &task_req)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        let response = PostTaskResponse {
+            id: task_ulid.to_string(),
+        };
+        if let Some(key) = idempotency_key {
+            let response = serde_json::to_string(&response).map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+            model::idempotency::save_idempotent_response(
+                &mut tx,
+                user_ulid,
+                key,
+                StatusCode::CREATED.as_u16(),
+                &response,
+            )
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
             HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
         })?;
 
-        Ok(HttpResponse::Created().finish())
+        Ok(HttpResponse::Created().json(response))
     }
 
-    post_task_inner(_req, body, session, pool)
+    post_task_inner(req, body, session, pool)
         .await
         .unwrap_or_else(std::convert::identity)
 }
@@ -231,39 +971,59 @@ pub async fn get_task(
         let task_ulid = ulid::Ulid::from_string(&id)
             .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
 
-        let task = model::tasks::get_task(pool.as_ref(), task_ulid)
+        let task = get_permitted_task(
+            pool.as_ref(),
+            user_ulid,
+            task_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let etag = task_etag(&task);
+        let task = to_task_response(pool.as_ref(), user_ulid, task)
             .await
             .map_err(|e| {
                 HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
-            })?
-            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+            })?;
 
-        if task.author_id != Some(ulid_to_binary(user_ulid).to_vec()) {
-            return Err(HttpResponse::Forbidden().body("Forbidden"));
-        }
-
-        Ok(
-            HttpResponse::Ok().json(TaskResponse::try_from(task).map_err(|e| {
-                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
-            })?),
-        )
-    }
+        Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(task))
+    }
 
     get_task_inner(_req, id, session, pool)
         .await
         .unwrap_or_else(std::convert::identity)
 }
 
+/// what happens to the subtasks of a task moved to the trash
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtaskDeletion {
+    /// delete the whole subtree
+    #[default]
+    Cascade,
+    /// move the direct subtasks up to the parent of the deleted task
+    Reparent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeleteTaskQuery {
+    #[serde(default)]
+    subtasks: SubtaskDeletion,
+}
+/// honors `If-Match` with the `ETag` of the task
 #[delete("/{id}")]
 pub async fn delete_task(
-    _req: HttpRequest,
+    req: HttpRequest,
     id: web::Path<String>,
+    query: web::Query<DeleteTaskQuery>,
     session: Session,
     pool: web::Data<sqlx::MySqlPool>,
 ) -> impl Responder {
     async fn delete_task_inner(
-        _req: HttpRequest,
+        req: HttpRequest,
         id: web::Path<String>,
+        query: web::Query<DeleteTaskQuery>,
         session: Session,
         pool: web::Data<sqlx::MySqlPool>,
     ) -> Result<HttpResponse, HttpResponse> {
@@ -278,33 +1038,91 @@ pub async fn delete_task(
         let task_ulid = ulid::Ulid::from_string(&id)
             .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
 
-        let task = model::tasks::get_task_with_lock(&mut tx, task_ulid)
+        let if_match = req.get_header::<IfMatch>();
+        trash_permitted_task(
+            &mut tx,
+            user_ulid,
+            task_ulid,
+            query.subtasks,
+            if_match.as_ref(),
+        )
+        .await?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_task_inner(req, id, query, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// moves the task to the trash for one of its owners, shared by `DELETE /tasks/{id}` and bulk
+/// deletions, returning the trashed tasks, the task first
+async fn trash_permitted_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+    subtasks: SubtaskDeletion,
+    if_match: Option<&IfMatch>,
+) -> Result<Vec<ulid::Ulid>, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let task = get_permitted_task(&mut *conn, user_ulid, task_ulid, ShareRole::Owner, true).await?;
+    check_if_match(if_match, &task)?;
+    notify_task_members(
+        &mut *conn,
+        user_ulid,
+        &task,
+        &format!("Deleted: {}", task.title),
+    )
+    .await?;
+
+    let deleted = match subtasks {
+        SubtaskDeletion::Cascade => model::tasks::get_subtasks(&mut *conn, task_ulid)
             .await
             .map_err(|e| {
                 HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
-            })?
-            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
-
-        if task.author_id != Some(ulid_to_binary(user_ulid).to_vec()) {
-            return Err(HttpResponse::Forbidden().body("Forbidden"));
+            })?,
+        SubtaskDeletion::Reparent => {
+            model::tasks::reparent_children(&mut *conn, task_ulid, task.parent_id.clone())
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+            Vec::new()
         }
-
-        model::tasks::delete_task(&mut tx, task_ulid)
+    };
+    let deleted = std::iter::once(task).chain(deleted).collect::<Vec<_>>();
+    for deleted in deleted.iter() {
+        model::history::record_deletion(&mut *conn, user_ulid, deleted)
             .await
             .map_err(|e| {
                 HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
             })?;
+    }
 
-        tx.commit().await.map_err(|e| {
+    let deleted_ulids = deleted
+        .iter()
+        .map(|task| binary_to_ulid(task.id.as_slice()))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| {
             HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
         })?;
 
-        Ok(HttpResponse::NoContent().finish())
-    }
-
-    delete_task_inner(_req, id, session, pool)
+    model::tasks::trash_tasks(&mut *conn, &deleted_ulids)
         .await
-        .unwrap_or_else(std::convert::identity)
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    Ok(deleted_ulids)
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -319,17 +1137,26 @@ pub struct PutTaskRequest {
     pub priority: Update<Option<TaskPriority>>,
     #[serde(default)]
     pub due_date: Update<Option<String>>,
+    #[serde(default)]
+    pub recurrence: Update<Option<Recurrence>>,
+    /// user id of the one doing the task, who must have access to it
+    #[serde(default)]
+    pub assignee_id: Update<Option<String>>,
+    /// start or finish the task even when its blockers are not done yet
+    #[serde(default)]
+    pub force: bool,
 }
+/// honors `If-Match` with the `ETag` of the task, and responds with the new one
 #[patch("/{id}")]
 pub async fn patch_task(
-    _req: HttpRequest,
+    req: HttpRequest,
     id: web::Path<String>,
     body: web::Json<PutTaskRequest>,
     session: Session,
     pool: web::Data<sqlx::MySqlPool>,
 ) -> impl Responder {
     async fn put_task_inner(
-        _req: HttpRequest,
+        req: HttpRequest,
         id: web::Path<String>,
         body: web::Json<PutTaskRequest>,
         session: Session,
@@ -346,37 +1173,829 @@ pub async fn patch_task(
         let task_ulid = ulid::Ulid::from_string(&id)
             .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
 
-        let task = model::tasks::get_task_with_lock(&mut tx, task_ulid)
+        let if_match = req.get_header::<IfMatch>();
+        update_permitted_task(&mut tx, user_ulid, task_ulid, &body, if_match.as_ref()).await?;
+
+        let task = model::tasks::get_task(&mut tx, task_ulid)
             .await
             .map_err(|e| {
                 HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
             })?
             .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
 
-        if task.author_id != Some(ulid_to_binary(user_ulid).to_vec()) {
-            return Err(HttpResponse::Forbidden().body("Forbidden"));
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent()
+            .insert_header(ETag(task_etag(&task)))
+            .finish())
+    }
+
+    put_task_inner(req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// applies `body` to the task for one of its editors, shared by `PATCH /tasks/{id}` and bulk
+/// updates
+async fn update_permitted_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+    body: &PutTaskRequest,
+    if_match: Option<&IfMatch>,
+) -> Result<(), HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let task =
+        get_permitted_task(&mut *conn, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+    check_if_match(if_match, &task)?;
+
+    if let Update::Set(state @ (TaskState::InProgress | TaskState::Done)) = body.state {
+        if state != task.state && !body.force {
+            let (blockers, hidden_blockers) =
+                model::dependencies::get_unfinished_blockers(&mut *conn, user_ulid, task_ulid)
+                    .await
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+            if !blockers.is_empty() || hidden_blockers > 0 {
+                let blockers = blockers
+                    .into_iter()
+                    .map(LinkedTaskResponse::try_from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+                return Err(HttpResponse::Conflict().json(UnfinishedBlockersResponse {
+                    blockers,
+                    hidden_blockers,
+                }));
+            }
         }
+    }
 
-        let task_req = model::tasks::UpdateTask {
-            title: body.title.clone(),
-            description: body.description.clone(),
-            state: body.state.clone(),
-            priority: body.priority.clone(),
-            due_date: body
-                .due_date
-                .clone()
-                .map(|d| {
-                    d.map(|s| {
-                        chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S").map_err(
-                            |e| HttpResponse::BadRequest().body(format!("Invalid due date: {}", e)),
-                        )
-                    })
+    let task_req = model::tasks::UpdateTask {
+        title: body.title.clone(),
+        description: body.description.clone(),
+        state: body.state.clone(),
+        priority: body.priority.clone(),
+        due_date: body
+            .due_date
+            .clone()
+            .map(|d| {
+                d.map(|s| chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S"))
                     .transpose()
-                })
-                .transpose()?,
+            })
+            .transpose()
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid due date: {}", e)))?,
+        recurrence: body.recurrence.clone(),
+        assignee_id: body
+            .assignee_id
+            .clone()
+            .map(|a| a.as_deref().map(ulid::Ulid::from_string).transpose())
+            .transpose()
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid assignee id: {}", e)))?
+            .map(|a| a.map(|a| ulid_to_binary(a).to_vec())),
+    };
+    if let Update::Set(Some(assignee_id)) = &task_req.assignee_id {
+        check_assignee(&mut *conn, &task, assignee_id).await?;
+    }
+
+    model::history::record_update(&mut *conn, user_ulid, &task, &task_req)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    if !task_req.is_nop() {
+        notify_task_members(
+            &mut *conn,
+            user_ulid,
+            &task,
+            &format!("Changed: {}", task.title),
+        )
+        .await?;
+    }
+
+    model::tasks::update_task(&mut *conn, task_ulid, task_req)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    // a recurring task hands its rule over to the next occurrence once done
+    if matches!(body.state, Update::Set(TaskState::Done)) && task.state != TaskState::Done {
+        let task = model::tasks::get_task(&mut *conn, task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+        if task.recurrence.is_some() {
+            let next = model::tasks::insert_next_occurrence(
+                &mut *conn,
+                &task,
+                chrono::Utc::now().naive_utc(),
+            )
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+            if let Some(next) = next {
+                model::history::record_creation(&mut *conn, user_ulid, &next)
+                    .await
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+            }
+
+            let task_req = model::tasks::UpdateTask {
+                recurrence: Update::Set(None),
+                ..Default::default()
+            };
+
+            model::history::record_update(&mut *conn, user_ulid, &task, &task_req)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+
+            model::tasks::update_task(&mut *conn, task_ulid, task_req)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+    }
+
+    Ok(())
+}
+
+#[put("/{id}/tags/{tag_id}")]
+pub async fn put_task_tag(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_task_tag_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (task_ulid, tag_ulid) = parse_task_tag_path(&path)?;
+
+        check_task_tag_owner(&mut tx, user_ulid, task_ulid, tag_ulid).await?;
+
+        model::tags::attach_tag(&mut tx, task_ulid, tag_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_task_tag_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[delete("/{id}/tags/{tag_id}")]
+pub async fn delete_task_tag(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_task_tag_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (task_ulid, tag_ulid) = parse_task_tag_path(&path)?;
+
+        check_task_tag_owner(&mut tx, user_ulid, task_ulid, tag_ulid).await?;
+
+        model::tags::detach_tag(&mut tx, task_ulid, tag_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_task_tag_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_assignee_id(assignee_id: &str) -> Result<Vec<u8>, HttpResponse> {
+    let assignee_ulid = ulid::Ulid::from_string(assignee_id)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid assignee id: {}", e)))?;
+
+    Ok(ulid_to_binary(assignee_ulid).to_vec())
+}
+
+/// checks that the assignee is a user who can see the task
+async fn check_assignee(
+    conn: impl Acquire<'_, Database = MySql>,
+    task: &Todo,
+    assignee_id: &[u8],
+) -> Result<(), HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let assignee_ulid = binary_to_ulid(assignee_id).map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let assignee = model::users::get_user(&mut *conn, assignee_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if assignee.is_none() {
+        return Err(HttpResponse::BadRequest().body("Invalid assignee: no such user"));
+    }
+
+    let role = model::shares::get_task_role(&mut *conn, assignee_ulid, task)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if role.is_none() {
+        return Err(HttpResponse::BadRequest().body("Invalid assignee: no access to the task"));
+    }
+
+    Ok(())
+}
+
+/// loads the task and checks that `user_ulid` has at least `role` on it, as its author or through
+/// a share of the task or of its project
+pub(crate) async fn get_permitted_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+    role: ShareRole,
+    with_lock: bool,
+) -> Result<Todo, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let task = if with_lock {
+        model::tasks::get_task_with_lock(&mut *conn, task_ulid).await
+    } else {
+        model::tasks::get_task(&mut *conn, task_ulid).await
+    }
+    .map_err(|e| HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e)))?
+    .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    let task_role = model::shares::get_task_role(&mut *conn, user_ulid, &task)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if task_role < Some(role) {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(task)
+}
+
+/// tells the others with access to the task that `user_ulid` changed it
+async fn notify_task_members(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task: &Todo,
+    message: &str,
+) -> Result<(), HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let member_ulids = model::shares::get_task_member_ids(&mut *conn, task)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    let task_ulid = binary_to_ulid(task.id.as_slice()).map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    for member_ulid in member_ulids.into_iter().filter(|m| *m != user_ulid) {
+        model::notifications::enqueue_notification(
+            &mut *conn,
+            member_ulid,
+            NotificationKind::TaskChanged,
+            Some(task_ulid),
+            message,
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    }
+
+    Ok(())
+}
+
+#[post("/{id}/subtasks")]
+pub async fn post_subtask(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PostTaskRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_subtask_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PostTaskRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let parent_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let parent =
+            get_permitted_task(&mut tx, user_ulid, parent_ulid, ShareRole::Editor, true).await?;
+
+        let task_ulid = body.to_task_ulid(&mut tx).await?;
+        let task_req = body.to_todo_req(
+            task_ulid,
+            user_ulid,
+            Some(parent_ulid),
+            parent.project_id,
+            parent.workspace_id,
+        )?;
+
+        model::tasks::insert_task(&mut tx, task_req.clone())
+            .await
+            .map_err(insert_task_error)?;
+        if let Some(assignee_id) = task_req.assignee_id.as_deref() {
+            let task = model::tasks::get_task(&mut tx, task_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?
+                .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+            check_assignee(&mut tx, &task, assignee_id).await?;
+        }
+
+        model::history::record_creation(&mut tx, user_ulid, &task_req)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().json(PostTaskResponse {
+            id: task_ulid.to_string(),
+        }))
+    }
+
+    post_subtask_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// moves the task to the project along with its subtasks
+async fn move_to_project(
+    conn: impl Acquire<'_, Database = MySql>,
+    task_ulid: ulid::Ulid,
+    project_id: Option<Vec<u8>>,
+) -> Result<(), HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let subtasks = model::tasks::get_subtasks(&mut *conn, task_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    let task_ulids = std::iter::once(Ok(task_ulid))
+        .chain(
+            subtasks
+                .iter()
+                .map(|task| binary_to_ulid(task.id.as_slice())),
+        )
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    model::tasks::set_project(&mut *conn, &task_ulids, project_id)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PutTaskProjectRequest {
+    /// `null` moves the task to the inbox
+    pub project_id: Option<String>,
+}
+/// subtasks follow the task, which leaves its parent when the parent stays in another project
+#[put("/{id}/project")]
+pub async fn put_task_project(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PutTaskProjectRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_task_project_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PutTaskProjectRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+        let project_ulid = body
+            .project_id
+            .as_deref()
+            .map(ulid::Ulid::from_string)
+            .transpose()
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid project id: {}", e)))?;
+
+        let task =
+            get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Owner, true).await?;
+
+        let project_id = match project_ulid {
+            Some(project_ulid) => Some(
+                get_permitted_project(&mut tx, user_ulid, project_ulid, ShareRole::Editor, true)
+                    .await?
+                    .id,
+            ),
+            None => None,
         };
+        if project_id == task.project_id {
+            return Ok(HttpResponse::NoContent().finish());
+        }
+
+        if task.parent_id.is_some() {
+            model::tasks::set_parent(&mut tx, task_ulid, None)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+
+        move_to_project(&mut tx, task_ulid, project_id).await?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_task_project_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PutTaskParentRequest {
+    /// `null` moves the task to the top level
+    pub parent_id: Option<String>,
+}
+#[put("/{id}/parent")]
+pub async fn put_task_parent(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PutTaskParentRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_task_parent_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PutTaskParentRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+        let parent_ulid = body
+            .parent_id
+            .as_deref()
+            .map(ulid::Ulid::from_string)
+            .transpose()
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid parent id: {}", e)))?;
+
+        let task =
+            get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+
+        if let Some(parent_ulid) = parent_ulid {
+            let parent =
+                get_permitted_task(&mut tx, user_ulid, parent_ulid, ShareRole::Editor, true)
+                    .await?;
+            if parent.workspace_id != task.workspace_id {
+                return Err(
+                    HttpResponse::BadRequest().body("Cannot move a task to another workspace")
+                );
+            }
+
+            let ancestor_ulids = model::tasks::get_ancestor_ids(&mut tx, parent_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+            if ancestor_ulids.contains(&task_ulid) {
+                return Err(HttpResponse::BadRequest()
+                    .body("Cannot move a task under itself or its subtasks"));
+            }
+
+            // moving the subtree to the project of the parent takes the same roles as
+            // `PUT /tasks/{id}/project`
+            if parent.project_id != task.project_id {
+                get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Owner, true).await?;
+                if let Some(project_id) = parent.project_id.as_deref() {
+                    let project_ulid = binary_to_ulid(project_id).map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+                    get_permitted_project(
+                        &mut tx,
+                        user_ulid,
+                        project_ulid,
+                        ShareRole::Editor,
+                        true,
+                    )
+                    .await?;
+                }
+                move_to_project(&mut tx, task_ulid, parent.project_id).await?;
+            }
+        }
+
+        model::tasks::set_parent(&mut tx, task_ulid, parent_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_task_parent_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTreeResponse {
+    #[serde(flatten)]
+    pub task: TaskResponse,
+    pub subtasks: Vec<TaskTreeResponse>,
+}
+impl TaskTreeResponse {
+    /// nests `descendants` under `root` by their `parent_id`
+    fn build(root: TaskResponse, descendants: Vec<TaskResponse>) -> Self {
+        let mut children: HashMap<String, Vec<TaskResponse>> = HashMap::new();
+        for task in descendants {
+            if let Some(parent_id) = task.parent_id.clone() {
+                children.entry(parent_id).or_default().push(task);
+            }
+        }
+
+        fn nest(
+            task: TaskResponse,
+            children: &mut HashMap<String, Vec<TaskResponse>>,
+        ) -> TaskTreeResponse {
+            let subtasks = children
+                .remove(&task.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|child| nest(child, children))
+                .collect();
+            TaskTreeResponse { task, subtasks }
+        }
+
+        nest(root, &mut children)
+    }
+}
 
-        model::tasks::update_task(&mut tx, task_ulid, task_req)
+#[get("/{id}/subtree")]
+pub async fn get_task_subtree(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_task_subtree_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let task = get_permitted_task(
+            pool.as_ref(),
+            user_ulid,
+            task_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let subtasks = model::tasks::get_visible_subtasks(pool.as_ref(), user_ulid, task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        let mut tasks =
+            to_task_responses(pool.as_ref(), user_ulid, [vec![task], subtasks].concat())
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        let root = tasks.remove(0);
+
+        Ok(HttpResponse::Ok().json(TaskTreeResponse::build(root, tasks)))
+    }
+
+    get_task_subtree_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[put("/{id}/blockers/{blocker_id}")]
+pub async fn put_task_blocker(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_task_blocker_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (task_ulid, blocker_ulid) = parse_task_blocker_path(&path)?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+        get_permitted_task(&mut tx, user_ulid, blocker_ulid, ShareRole::Editor, true).await?;
+
+        if task_ulid == blocker_ulid {
+            return Err(HttpResponse::BadRequest().body("A task cannot block itself"));
+        }
+        let is_cycle =
+            model::dependencies::is_blocked_transitively(&mut tx, blocker_ulid, task_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        if is_cycle {
+            return Err(HttpResponse::BadRequest().body(format!(
+                "Dependency cycle: {} is already blocked by {}",
+                blocker_ulid, task_ulid
+            )));
+        }
+
+        model::dependencies::add_blocker(&mut tx, task_ulid, blocker_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_task_blocker_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[delete("/{id}/blockers/{blocker_id}")]
+pub async fn delete_task_blocker(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_task_blocker_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (task_ulid, blocker_ulid) = parse_task_blocker_path(&path)?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+
+        model::dependencies::remove_blocker(&mut tx, task_ulid, blocker_ulid)
             .await
             .map_err(|e| {
                 HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
@@ -389,7 +2008,60 @@ pub async fn patch_task(
         Ok(HttpResponse::NoContent().finish())
     }
 
-    put_task_inner(_req, id, body, session, pool)
+    delete_task_blocker_inner(_req, path, session, pool)
         .await
         .unwrap_or_else(std::convert::identity)
 }
+
+#[allow(clippy::result_large_err)]
+fn parse_task_blocker_path(
+    path: &(String, String),
+) -> Result<(ulid::Ulid, ulid::Ulid), HttpResponse> {
+    let task_ulid = ulid::Ulid::from_string(&path.0)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+    let blocker_ulid = ulid::Ulid::from_string(&path.1)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid blocker id: {}", e)))?;
+
+    Ok((task_ulid, blocker_ulid))
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_task_tag_path(path: &(String, String)) -> Result<(ulid::Ulid, ulid::Ulid), HttpResponse> {
+    let task_ulid = ulid::Ulid::from_string(&path.0)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+    let tag_ulid = ulid::Ulid::from_string(&path.1)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid tag id: {}", e)))?;
+
+    Ok((task_ulid, tag_ulid))
+}
+
+async fn check_task_tag_owner(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+    tag_ulid: ulid::Ulid,
+) -> Result<(), HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let task =
+        get_permitted_task(&mut *conn, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+
+    let tag = model::tags::get_tag(&mut *conn, tag_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .ok_or_else(|| HttpResponse::NotFound().body("Tag Not Found"))?;
+    // personal tags of the user, or the tags of the workspace of the task
+    let is_permitted = match &tag.workspace_id {
+        Some(workspace_id) => task.workspace_id.as_ref() == Some(workspace_id),
+        None => tag.author_id == ulid_to_binary(user_ulid).to_vec(),
+    };
+    if !is_permitted {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(())
+}
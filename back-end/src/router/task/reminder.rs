@@ -0,0 +1,277 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use super::get_permitted_task;
+use crate::{
+    model::{
+        self,
+        types::{Reminder, ReminderChannel, ReminderReq, ShareRole},
+    },
+    scheduler::{parse_email_address, parse_webhook_url},
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderResponse {
+    pub id: String,
+    pub task_id: String,
+    pub remind_at: Option<String>,
+    pub offset_minutes: Option<i32>,
+    pub channel: ReminderChannel,
+    pub target: Option<String>,
+    pub fired_at: Option<String>,
+    pub attempts: i32,
+    /// not retried before this time after a failed delivery
+    pub locked_until: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+impl TryFrom<Reminder> for ReminderResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Reminder) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let task_id = binary_to_ulid(value.todo_id.as_slice())?;
+        let remind_at = value
+            .remind_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let fired_at = value
+            .fired_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let locked_until = value
+            .locked_until
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            task_id: task_id.to_string(),
+            remind_at,
+            offset_minutes: value.offset_minutes,
+            channel: value.channel,
+            target: value.target,
+            fired_at,
+            attempts: value.attempts,
+            locked_until,
+            last_error: value.last_error,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+/// only the reminders of the user, which may carry their webhook URL or email address
+#[get("/{id}/reminders")]
+pub async fn get_task_reminders(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_task_reminders_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        get_permitted_task(
+            pool.as_ref(),
+            user_ulid,
+            task_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let reminders =
+            model::reminders::get_reminders_of_task(pool.as_ref(), task_ulid, user_ulid)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?
+                .into_iter()
+                .map(ReminderResponse::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+
+        Ok(HttpResponse::Ok().json(reminders))
+    }
+
+    get_task_reminders_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostReminderRequest {
+    /// `%Y-%m-%d %H:%M:%S`, exclusive with `offset_minutes`
+    pub remind_at: Option<String>,
+    /// minutes before the due date of the task, following it when the due date changes
+    pub offset_minutes: Option<i32>,
+    pub channel: ReminderChannel,
+    /// public `http://` or `https://` URL for webhooks, address for emails
+    pub target: Option<String>,
+}
+#[post("/{id}/reminders")]
+pub async fn post_task_reminder(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PostReminderRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_task_reminder_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PostReminderRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+
+        let remind_at = body
+            .remind_at
+            .as_ref()
+            .map(|d| chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S"))
+            .transpose()
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid remind_at: {}", e)))?;
+        if remind_at.is_some() == body.offset_minutes.is_some() {
+            return Err(
+                HttpResponse::BadRequest().body("Either remind_at or offset_minutes is required")
+            );
+        }
+        if matches!(body.offset_minutes, Some(offset) if offset < 0) {
+            return Err(HttpResponse::BadRequest().body("Invalid offset_minutes"));
+        }
+
+        let target = body.target.as_ref().map(|t| t.trim().to_string());
+        match (body.channel, target.as_deref()) {
+            (ReminderChannel::InApp, None) => (),
+            (ReminderChannel::Webhook, Some(url)) => {
+                parse_webhook_url(url).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid target: {}", e))
+                })?;
+            }
+            (ReminderChannel::Email, Some(address)) => {
+                parse_email_address(address).map_err(|e| {
+                    HttpResponse::BadRequest().body(format!("Invalid target: {}", e))
+                })?;
+            }
+            _ => return Err(HttpResponse::BadRequest().body("Invalid target")),
+        }
+
+        let reminder_ulid = ulid::Ulid::new();
+
+        model::reminders::insert_reminder(
+            &mut tx,
+            ReminderReq {
+                id: ulid_to_binary(reminder_ulid).to_vec(),
+                todo_id: ulid_to_binary(task_ulid).to_vec(),
+                author_id: ulid_to_binary(user_ulid).to_vec(),
+                remind_at,
+                offset_minutes: body.offset_minutes,
+                channel: body.channel,
+                target,
+            },
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().finish())
+    }
+
+    post_task_reminder_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// editors delete their own reminders, the owners of the task any of them
+#[delete("/{id}/reminders/{reminder_id}")]
+pub async fn delete_task_reminder(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_task_reminder_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&path.0)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+        let reminder_ulid = ulid::Ulid::from_string(&path.1)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid reminder id: {}", e)))?;
+
+        get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Editor, true).await?;
+
+        let reminder = model::reminders::get_reminder(&mut tx, reminder_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+        if reminder.todo_id != ulid_to_binary(task_ulid).to_vec() {
+            return Err(HttpResponse::NotFound().body("Not Found"));
+        }
+        if reminder.author_id != ulid_to_binary(user_ulid).to_vec() {
+            get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Owner, true).await?;
+        }
+
+        model::reminders::delete_reminder(&mut tx, reminder_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_task_reminder_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
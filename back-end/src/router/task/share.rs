@@ -0,0 +1,224 @@
+use actix_session::Session;
+use actix_web::{delete, get, put, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, MySql};
+
+use super::get_permitted_task;
+use crate::{
+    model::{
+        self,
+        types::{Share, ShareRole, User},
+    },
+    utils::{binary_to_ulid, check_is_logged_in},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareResponse {
+    pub user_id: String,
+    /// `None` once the user has been deleted
+    pub username: Option<String>,
+    pub role: ShareRole,
+    pub created_at: String,
+}
+impl TryFrom<Share> for ShareResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Share) -> Result<Self, Self::Error> {
+        let user_id = binary_to_ulid(value.user_id.as_slice())?;
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            user_id: user_id.to_string(),
+            username: value.username,
+            role: value.role,
+            created_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutShareRequest {
+    pub role: ShareRole,
+}
+
+/// the user behind `username`, `Not Found` once deleted unless `include_deleted`
+pub(crate) async fn get_share_user(
+    conn: impl Acquire<'_, Database = MySql>,
+    username: &str,
+    include_deleted: bool,
+) -> Result<User, HttpResponse> {
+    let user = model::users::get_user_from_username(conn, username)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .filter(|user| include_deleted || user.deleted_at.is_none())
+        .ok_or_else(|| HttpResponse::NotFound().body("User Not Found"))?;
+
+    Ok(user)
+}
+
+#[get("/{id}/shares")]
+pub async fn get_task_shares(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_task_shares_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        get_permitted_task(
+            pool.as_ref(),
+            user_ulid,
+            task_ulid,
+            ShareRole::Viewer,
+            false,
+        )
+        .await?;
+
+        let shares = model::shares::get_task_shares(pool.as_ref(), task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(ShareResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(shares))
+    }
+
+    get_task_shares_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// only the owners of the task share it, replacing the role the user had
+#[put("/{id}/shares/{username}")]
+pub async fn put_task_share(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    body: web::Json<PutShareRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_task_share_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        body: web::Json<PutShareRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, username) = path.into_inner();
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let task =
+            get_permitted_task(&mut tx, user_ulid, task_ulid, ShareRole::Owner, true).await?;
+
+        let target = get_share_user(&mut tx, &username, false).await?;
+        if task.author_id.as_ref() == Some(&target.id) {
+            return Err(HttpResponse::BadRequest().body("Cannot share with the author"));
+        }
+        let target_ulid = binary_to_ulid(target.id.as_slice()).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+        if target_ulid == user_ulid {
+            return Err(HttpResponse::BadRequest().body("Cannot share with yourself"));
+        }
+
+        model::shares::share_task(&mut tx, task_ulid, target_ulid, body.role)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_task_share_inner(_req, path, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// only the owners of the task unshare it, except for the users leaving it themselves
+#[delete("/{id}/shares/{username}")]
+pub async fn delete_task_share(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_task_share_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, username) = path.into_inner();
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let target = get_share_user(&mut tx, &username, true).await?;
+        let target_ulid = binary_to_ulid(target.id.as_slice()).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let role = if target_ulid == user_ulid {
+            ShareRole::Viewer
+        } else {
+            ShareRole::Owner
+        };
+        get_permitted_task(&mut tx, user_ulid, task_ulid, role, true).await?;
+
+        model::shares::unshare_task(&mut tx, task_ulid, target_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_task_share_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
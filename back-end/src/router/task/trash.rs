@@ -0,0 +1,206 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{Acquire, MySql};
+
+use super::to_task_responses;
+use crate::{
+    model::{
+        self,
+        types::{ShareRole, Todo},
+    },
+    utils::check_is_logged_in,
+};
+
+/// loads the task from the trash and checks that `user_ulid` is one of its owners, like
+/// `get_permitted_task` does for the tasks out of the trash
+async fn get_owned_trashed_task(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    task_ulid: ulid::Ulid,
+) -> Result<Todo, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let task = model::tasks::get_trashed_task_with_lock(&mut *conn, task_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    let role = model::shares::get_task_role(&mut *conn, user_ulid, &task)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if role < Some(ShareRole::Owner) {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(task)
+}
+
+/// tasks in the trash which the user owns, most recently deleted first
+#[get("/trash")]
+pub async fn get_trash(
+    _req: HttpRequest,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_trash_inner(
+        _req: HttpRequest,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let tasks = model::tasks::get_trashed_tasks(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        let tasks = to_task_responses(pool.as_ref(), user_ulid, tasks)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(tasks))
+    }
+
+    get_trash_inner(_req, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// permanently deletes every task in the trash which the user owns
+#[delete("/trash")]
+pub async fn delete_trash(
+    _req: HttpRequest,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_trash_inner(
+        _req: HttpRequest,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        model::tasks::purge_trash(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_trash_inner(_req, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// permanently deletes the task from the trash
+#[delete("/trash/{id}")]
+pub async fn delete_trashed_task(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_trashed_task_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        get_owned_trashed_task(&mut tx, user_ulid, task_ulid).await?;
+
+        model::tasks::purge_task(&mut tx, task_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_trashed_task_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// takes the task out of the trash with the subtasks deleted along with it
+#[post("/{id}/restore")]
+pub async fn post_task_restore(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_task_restore_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let task_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid task id: {}", e)))?;
+
+        let task = get_owned_trashed_task(&mut tx, user_ulid, task_ulid).await?;
+
+        let restored = model::tasks::restore_task(&mut tx, &task)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        for restored in restored.iter() {
+            model::history::record_restoration(&mut tx, user_ulid, restored)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    post_task_restore_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
@@ -0,0 +1,367 @@
+use actix_session::Session;
+use actix_web::{
+    delete, dev::HttpServiceFactory, get, patch, post, web, HttpRequest, HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{list_tasks, GetTaskQuery, TaskResponse};
+use crate::{
+    model::{
+        self,
+        types::{TaskView, TaskViewReq, VecWithTotal},
+        Update,
+    },
+    utils::{binary_to_ulid, check_is_logged_in, ulid_to_binary},
+};
+
+pub fn views_router() -> impl HttpServiceFactory {
+    web::scope("/views")
+        .service(post_view)
+        .service(get_views_me)
+        .service(get_view_tasks)
+        .service(patch_view)
+        .service(delete_view)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewResponse {
+    pub id: String,
+    pub name: String,
+    pub query: GetTaskQuery,
+    pub created_at: String,
+    pub updated_at: String,
+}
+impl TryFrom<TaskView> for ViewResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TaskView) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let query = serde_json::from_str(&value.query)?;
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            name: value.name,
+            query,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+/// checks that `query` lists tasks without errors and serializes it for storing
+///
+/// cursors point into a specific result page, so they are not kept in a view
+#[allow(clippy::result_large_err)]
+fn to_stored_query(mut query: GetTaskQuery) -> Result<String, HttpResponse> {
+    query.cursor = None;
+
+    let sorted_by = query.to_sorted_by()?.unwrap_or_default();
+    query.to_limit(&sorted_by)?;
+    query.to_filter()?;
+
+    serde_json::to_string(&query).map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })
+}
+
+/// loads the view and checks that it belongs to `user_ulid`
+pub(crate) async fn get_own_view(
+    conn: impl sqlx::Acquire<'_, Database = sqlx::MySql>,
+    user_ulid: ulid::Ulid,
+    view_ulid: ulid::Ulid,
+    with_lock: bool,
+) -> Result<TaskView, HttpResponse> {
+    let view = if with_lock {
+        model::views::get_view_with_lock(conn, view_ulid).await
+    } else {
+        model::views::get_view(conn, view_ulid).await
+    }
+    .map_err(|e| HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e)))?
+    .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    if view.author_id != ulid_to_binary(user_ulid).to_vec() {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(view)
+}
+
+#[get("/me")]
+pub async fn get_views_me(session: Session, pool: web::Data<sqlx::MySqlPool>) -> impl Responder {
+    async fn get_views_me_inner(
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let views = model::views::get_views(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(ViewResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(views))
+    }
+
+    get_views_me_inner(session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostViewRequest {
+    pub name: String,
+    #[serde(default)]
+    pub query: GetTaskQuery,
+}
+#[post("")]
+pub async fn post_view(
+    _req: HttpRequest,
+    body: web::Json<PostViewRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_view_inner(
+        _req: HttpRequest,
+        body: web::Json<PostViewRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let name = body.name.trim().to_string();
+        if name.is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid view name"));
+        }
+
+        let is_exists = model::views::is_view_name_exists(&mut tx, user_ulid, &name)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        if is_exists {
+            return Err(HttpResponse::BadRequest().body("View already exists"));
+        }
+
+        let query = to_stored_query(body.query.clone())?;
+
+        let view_ulid = ulid::Ulid::new();
+
+        model::views::insert_view(
+            &mut tx,
+            TaskViewReq {
+                id: ulid_to_binary(view_ulid).to_vec(),
+                author_id: ulid_to_binary(user_ulid).to_vec(),
+                name,
+                query,
+            },
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().finish())
+    }
+
+    post_view_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// pagination of a view, replacing the one saved in it
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetViewTasksQuery {
+    limit: Option<usize>,
+    offset: Option<usize>,
+    cursor: Option<String>,
+}
+
+/// tasks of the view as seen by `user_ulid`
+pub(crate) async fn list_view_tasks(
+    conn: impl sqlx::Acquire<'_, Database = sqlx::MySql>,
+    user_ulid: ulid::Ulid,
+    view: &TaskView,
+    query: &GetViewTasksQuery,
+) -> Result<VecWithTotal<TaskResponse>, HttpResponse> {
+    let mut task_query: GetTaskQuery = serde_json::from_str(&view.query).map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+    if query.limit.is_some() {
+        task_query.limit = query.limit;
+        task_query.offset = None;
+    }
+    if query.offset.is_some() {
+        task_query.offset = query.offset;
+    }
+    task_query.cursor = query.cursor.clone();
+
+    list_tasks(conn, user_ulid, &task_query).await
+}
+#[get("/{id}")]
+pub async fn get_view_tasks(
+    id: web::Path<String>,
+    query: web::Query<GetViewTasksQuery>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_view_tasks_inner(
+        id: web::Path<String>,
+        query: web::Query<GetViewTasksQuery>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let view_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid view id: {}", e)))?;
+
+        let view = get_own_view(pool.as_ref(), user_ulid, view_ulid, false).await?;
+
+        let tasks = list_view_tasks(pool.as_ref(), user_ulid, &view, &query).await?;
+
+        Ok(HttpResponse::Ok().json(tasks))
+    }
+
+    get_view_tasks_inner(id, query, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatchViewRequest {
+    #[serde(default)]
+    pub name: Update<String>,
+    #[serde(default)]
+    pub query: Update<GetTaskQuery>,
+}
+#[patch("/{id}")]
+pub async fn patch_view(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PatchViewRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn patch_view_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PatchViewRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let view_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid view id: {}", e)))?;
+
+        let view = get_own_view(&mut tx, user_ulid, view_ulid, true).await?;
+
+        let name = body.name.clone().map(|name| name.trim().to_string());
+        if let Update::Set(name) = &name {
+            if name.is_empty() {
+                return Err(HttpResponse::BadRequest().body("Invalid view name"));
+            }
+            if name != &view.name {
+                let is_exists = model::views::is_view_name_exists(&mut tx, user_ulid, name)
+                    .await
+                    .map_err(|e| {
+                        HttpResponse::InternalServerError()
+                            .body(format!("Internal Server Error: {}", e))
+                    })?;
+                if is_exists {
+                    return Err(HttpResponse::BadRequest().body("View already exists"));
+                }
+            }
+        }
+        let query = body.query.clone().map(to_stored_query).transpose()?;
+
+        model::views::update_view(&mut tx, view_ulid, model::views::UpdateView { name, query })
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    patch_view_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[delete("/{id}")]
+pub async fn delete_view(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_view_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let view_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid view id: {}", e)))?;
+
+        get_own_view(&mut tx, user_ulid, view_ulid, true).await?;
+
+        model::views::delete_view(&mut tx, view_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_view_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
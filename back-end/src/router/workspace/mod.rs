@@ -0,0 +1,891 @@
+use actix_session::Session;
+use actix_web::{
+    delete, dev::HttpServiceFactory, get, patch, post, put, web, HttpRequest, HttpResponse,
+    Responder,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, MySql};
+
+use crate::{
+    model::{
+        self,
+        types::{Workspace, WorkspaceInvitation, WorkspaceMember, WorkspaceRole},
+    },
+    router::task::share::get_share_user,
+    utils::{binary_to_ulid, check_is_logged_in, generate_token},
+};
+
+/// tasks and tags created in a workspace are seen by all of its members
+pub fn workspaces_router() -> impl HttpServiceFactory {
+    web::scope("/workspaces")
+        .service(post_workspace)
+        .service(get_workspaces_me)
+        .service(get_workspace)
+        .service(patch_workspace)
+        .service(delete_workspace)
+        .service(get_workspace_members)
+        .service(put_workspace_member)
+        .service(delete_workspace_member)
+        .service(get_workspace_invitations)
+        .service(post_workspace_invitation)
+        .service(delete_workspace_invitation)
+        .service(post_workspace_invitation_accept)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceResponse {
+    pub id: String,
+    pub name: String,
+    /// role of the current user
+    pub role: Option<WorkspaceRole>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+impl TryFrom<Workspace> for WorkspaceResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Workspace) -> Result<Self, Self::Error> {
+        let id = binary_to_ulid(value.id.as_slice())?;
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let updated_at = value.updated_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            id: id.to_string(),
+            name: value.name,
+            role: value.role,
+            created_at,
+            updated_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberResponse {
+    pub user_id: String,
+    /// `None` once the user has been deleted
+    pub username: Option<String>,
+    pub role: WorkspaceRole,
+    pub created_at: String,
+}
+impl TryFrom<WorkspaceMember> for MemberResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: WorkspaceMember) -> Result<Self, Self::Error> {
+        let user_id = binary_to_ulid(value.user_id.as_slice())?;
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            user_id: user_id.to_string(),
+            username: value.username,
+            role: value.role,
+            created_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitationResponse {
+    pub token: String,
+    pub workspace_id: String,
+    pub inviter_id: String,
+    pub role: WorkspaceRole,
+    pub expires_at: String,
+    pub created_at: String,
+}
+impl TryFrom<WorkspaceInvitation> for InvitationResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(value: WorkspaceInvitation) -> Result<Self, Self::Error> {
+        let workspace_id = binary_to_ulid(value.workspace_id.as_slice())?;
+        let inviter_id = binary_to_ulid(value.inviter_id.as_slice())?;
+        let expires_at = value.expires_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let created_at = value.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Ok(Self {
+            token: value.token,
+            workspace_id: workspace_id.to_string(),
+            inviter_id: inviter_id.to_string(),
+            role: value.role,
+            expires_at,
+            created_at,
+        })
+    }
+}
+
+/// loads the workspace, with the role of `user_ulid` in it, and checks that the role is at
+/// least `role`
+pub(crate) async fn get_permitted_workspace(
+    conn: impl Acquire<'_, Database = MySql>,
+    user_ulid: ulid::Ulid,
+    workspace_ulid: ulid::Ulid,
+    role: WorkspaceRole,
+    with_lock: bool,
+) -> Result<Workspace, HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let mut workspace = if with_lock {
+        model::workspaces::get_workspace_with_lock(&mut *conn, workspace_ulid).await
+    } else {
+        model::workspaces::get_workspace(&mut *conn, workspace_ulid).await
+    }
+    .map_err(|e| HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e)))?
+    .ok_or_else(|| HttpResponse::NotFound().body("Not Found"))?;
+
+    workspace.role = model::workspaces::get_workspace_role(&mut *conn, workspace_ulid, user_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if workspace.role < Some(role) {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+
+    Ok(workspace)
+}
+
+/// the role of the member behind `username`
+async fn get_member(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_ulid: ulid::Ulid,
+    username: &str,
+) -> Result<(ulid::Ulid, WorkspaceRole), HttpResponse> {
+    let mut conn = conn.acquire().await.map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let user = get_share_user(&mut *conn, username, true).await?;
+    let user_ulid = binary_to_ulid(user.id.as_slice()).map_err(|e| {
+        HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+    })?;
+
+    let role = model::workspaces::get_workspace_role(&mut *conn, workspace_ulid, user_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?
+        .ok_or_else(|| HttpResponse::NotFound().body("Member Not Found"))?;
+
+    Ok((user_ulid, role))
+}
+
+/// `Conflict` when the owner is the last one, a workspace always keeps an owner
+async fn check_other_owner(
+    conn: impl Acquire<'_, Database = MySql>,
+    workspace_ulid: ulid::Ulid,
+) -> Result<(), HttpResponse> {
+    let owners = model::workspaces::count_owners(conn, workspace_ulid)
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+    if owners <= 1 {
+        return Err(HttpResponse::Conflict().body("Last owner of the workspace"));
+    }
+
+    Ok(())
+}
+
+#[get("/me")]
+pub async fn get_workspaces_me(
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_workspaces_me_inner(
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspaces = model::workspaces::get_workspaces(pool.as_ref(), user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(WorkspaceResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(workspaces))
+    }
+
+    get_workspaces_me_inner(session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[get("/{id}")]
+pub async fn get_workspace(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_workspace_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        let workspace = get_permitted_workspace(
+            pool.as_ref(),
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Member,
+            false,
+        )
+        .await?;
+
+        let workspace = WorkspaceResponse::try_from(workspace).map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Ok().json(workspace))
+    }
+
+    get_workspace_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostWorkspaceRequest {
+    pub name: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostWorkspaceResponse {
+    pub id: String,
+}
+/// the user becomes the owner of the new workspace
+#[post("")]
+pub async fn post_workspace(
+    _req: HttpRequest,
+    body: web::Json<PostWorkspaceRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_workspace_inner(
+        _req: HttpRequest,
+        body: web::Json<PostWorkspaceRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let name = body.name.trim();
+        if name.is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid workspace name"));
+        }
+
+        let workspace_ulid = ulid::Ulid::new();
+
+        model::workspaces::insert_workspace(&mut tx, workspace_ulid, name, user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().json(PostWorkspaceResponse {
+            id: workspace_ulid.to_string(),
+        }))
+    }
+
+    post_workspace_inner(_req, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchWorkspaceRequest {
+    pub name: String,
+}
+#[patch("/{id}")]
+pub async fn patch_workspace(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PatchWorkspaceRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn patch_workspace_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PatchWorkspaceRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        get_permitted_workspace(
+            &mut tx,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Admin,
+            true,
+        )
+        .await?;
+
+        let name = body.name.trim();
+        if name.is_empty() {
+            return Err(HttpResponse::BadRequest().body("Invalid workspace name"));
+        }
+
+        model::workspaces::rename_workspace(&mut tx, workspace_ulid, name)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    patch_workspace_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// the tasks and the tags of the workspace are deleted along with it
+#[delete("/{id}")]
+pub async fn delete_workspace(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_workspace_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        get_permitted_workspace(
+            &mut tx,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Owner,
+            true,
+        )
+        .await?;
+
+        model::workspaces::delete_workspace(&mut tx, workspace_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_workspace_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[get("/{id}/members")]
+pub async fn get_workspace_members(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_workspace_members_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        get_permitted_workspace(
+            pool.as_ref(),
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Member,
+            false,
+        )
+        .await?;
+
+        let members = model::workspaces::get_members(pool.as_ref(), workspace_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(MemberResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(members))
+    }
+
+    get_workspace_members_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutMemberRequest {
+    pub role: WorkspaceRole,
+}
+/// changes the role of a member, admins manage the members and owners manage the owners
+#[put("/{id}/members/{username}")]
+pub async fn put_workspace_member(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    body: web::Json<PutMemberRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn put_workspace_member_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        body: web::Json<PutMemberRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, username) = path.into_inner();
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        let workspace = get_permitted_workspace(
+            &mut tx,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Admin,
+            true,
+        )
+        .await?;
+
+        let (member_ulid, member_role) = get_member(&mut tx, workspace_ulid, &username).await?;
+        if member_role == body.role {
+            return Ok(HttpResponse::NoContent().finish());
+        }
+        if (member_role == WorkspaceRole::Owner || body.role == WorkspaceRole::Owner)
+            && workspace.role != Some(WorkspaceRole::Owner)
+        {
+            return Err(HttpResponse::Forbidden().body("Forbidden"));
+        }
+        if member_role == WorkspaceRole::Owner {
+            check_other_owner(&mut tx, workspace_ulid).await?;
+        }
+
+        model::workspaces::set_member_role(&mut tx, workspace_ulid, member_ulid, body.role)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    put_workspace_member_inner(_req, path, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// removes a member, which every member may do for themselves
+#[delete("/{id}/members/{username}")]
+pub async fn delete_workspace_member(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_workspace_member_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, username) = path.into_inner();
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        let workspace = get_permitted_workspace(
+            &mut tx,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Member,
+            true,
+        )
+        .await?;
+
+        let (member_ulid, member_role) = get_member(&mut tx, workspace_ulid, &username).await?;
+        if member_ulid != user_ulid {
+            let required = match member_role {
+                WorkspaceRole::Owner => WorkspaceRole::Owner,
+                _ => WorkspaceRole::Admin,
+            };
+            if workspace.role < Some(required) {
+                return Err(HttpResponse::Forbidden().body("Forbidden"));
+            }
+        }
+        if member_role == WorkspaceRole::Owner {
+            check_other_owner(&mut tx, workspace_ulid).await?;
+        }
+
+        model::workspaces::remove_member(&mut tx, workspace_ulid, member_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_workspace_member_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[get("/{id}/invitations")]
+pub async fn get_workspace_invitations(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn get_workspace_invitations_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let user_ulid = check_is_logged_in(session, pool.as_ref())
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        get_permitted_workspace(
+            pool.as_ref(),
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Admin,
+            false,
+        )
+        .await?;
+
+        let invitations = model::workspaces::get_invitations(pool.as_ref(), workspace_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .into_iter()
+            .map(InvitationResponse::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        Ok(HttpResponse::Ok().json(invitations))
+    }
+
+    get_workspace_invitations_inner(_req, id, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostInvitationRequest {
+    /// given to whoever accepts the invitation, defaults to `member`
+    #[serde(default)]
+    pub role: Option<WorkspaceRole>,
+    /// defaults to 7, at most 30
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+}
+/// the token is to be handed over to the invitee, who accepts it once with their account
+#[post("/{id}/invitations")]
+pub async fn post_workspace_invitation(
+    _req: HttpRequest,
+    id: web::Path<String>,
+    body: web::Json<PostInvitationRequest>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_workspace_invitation_inner(
+        _req: HttpRequest,
+        id: web::Path<String>,
+        body: web::Json<PostInvitationRequest>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        let workspace = get_permitted_workspace(
+            &mut tx,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Admin,
+            false,
+        )
+        .await?;
+
+        let role = body.role.unwrap_or(WorkspaceRole::Member);
+        if role == WorkspaceRole::Owner && workspace.role != Some(WorkspaceRole::Owner) {
+            return Err(HttpResponse::Forbidden().body("Forbidden"));
+        }
+        let expires_in_days = body.expires_in_days.unwrap_or(7);
+        if !(1..=30).contains(&expires_in_days) {
+            return Err(HttpResponse::BadRequest().body("Invalid expires_in_days"));
+        }
+        let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::days(expires_in_days);
+
+        let token = generate_token();
+
+        model::workspaces::insert_invitation(
+            &mut tx,
+            &token,
+            workspace_ulid,
+            user_ulid,
+            role,
+            expires_at,
+        )
+        .await
+        .map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::Created().json(InvitationResponse {
+            token,
+            workspace_id: workspace_ulid.to_string(),
+            inviter_id: user_ulid.to_string(),
+            role,
+            expires_at: expires_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            created_at: chrono::Utc::now()
+                .naive_utc()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        }))
+    }
+
+    post_workspace_invitation_inner(_req, id, body, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// revokes the invitation
+#[delete("/{id}/invitations/{token}")]
+pub async fn delete_workspace_invitation(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn delete_workspace_invitation_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, token) = path.into_inner();
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        get_permitted_workspace(
+            &mut tx,
+            user_ulid,
+            workspace_ulid,
+            WorkspaceRole::Admin,
+            false,
+        )
+        .await?;
+
+        model::workspaces::get_invitation_with_lock(&mut tx, workspace_ulid, &token)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?
+            .ok_or_else(|| HttpResponse::NotFound().body("Invitation Not Found"))?;
+
+        model::workspaces::delete_invitation(&mut tx, &token)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    delete_workspace_invitation_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
+
+/// the current user joins the workspace with the role of the invitation, which can be accepted
+/// only once
+#[post("/{id}/invitations/{token}/accept")]
+pub async fn post_workspace_invitation_accept(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    session: Session,
+    pool: web::Data<sqlx::MySqlPool>,
+) -> impl Responder {
+    async fn post_workspace_invitation_accept_inner(
+        _req: HttpRequest,
+        path: web::Path<(String, String)>,
+        session: Session,
+        pool: web::Data<sqlx::MySqlPool>,
+    ) -> Result<HttpResponse, HttpResponse> {
+        let mut tx = pool.begin().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        let user_ulid = check_is_logged_in(session, &mut tx)
+            .await
+            .map_err(|e| HttpResponse::Unauthorized().body(format!("Unauthorized: {}", e)))?;
+
+        let (id, token) = path.into_inner();
+        let workspace_ulid = ulid::Ulid::from_string(&id)
+            .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid workspace id: {}", e)))?;
+
+        let invitation =
+            model::workspaces::get_invitation_with_lock(&mut tx, workspace_ulid, &token)
+                .await
+                .map_err(|e| {
+                    HttpResponse::InternalServerError()
+                        .body(format!("Internal Server Error: {}", e))
+                })?
+                .ok_or_else(|| HttpResponse::NotFound().body("Invitation Not Found"))?;
+
+        let role = model::workspaces::get_workspace_role(&mut tx, workspace_ulid, user_ulid)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+        if role.is_some() {
+            return Err(HttpResponse::Conflict().body("Already a member of the workspace"));
+        }
+
+        model::workspaces::set_member_role(&mut tx, workspace_ulid, user_ulid, invitation.role)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        model::workspaces::delete_invitation(&mut tx, &token)
+            .await
+            .map_err(|e| {
+                HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            HttpResponse::InternalServerError().body(format!("Internal Server Error: {}", e))
+        })?;
+
+        Ok(HttpResponse::NoContent().finish())
+    }
+
+    post_workspace_invitation_accept_inner(_req, path, session, pool)
+        .await
+        .unwrap_or_else(std::convert::identity)
+}
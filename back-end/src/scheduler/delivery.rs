@@ -0,0 +1,361 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, Address, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::{
+    model::{
+        self,
+        types::{DueReminder, NotificationKind, ReminderChannel},
+    },
+    utils::binary_to_ulid,
+};
+
+/// sends a fired reminder to its user
+#[async_trait::async_trait]
+pub trait Delivery: Send + Sync {
+    fn channel(&self) -> ReminderChannel;
+
+    /// an error leaves the reminder to be retried later
+    async fn deliver(&self, reminder: &DueReminder) -> anyhow::Result<()>;
+}
+
+/// JSON describing the reminder, sent by webhooks
+fn payload(reminder: &DueReminder) -> anyhow::Result<serde_json::Value> {
+    let reminder_id = binary_to_ulid(reminder.reminder.id.as_slice())?;
+    let task_id = binary_to_ulid(reminder.reminder.todo_id.as_slice())?;
+    let user_id = binary_to_ulid(reminder.reminder.author_id.as_slice())?;
+
+    Ok(serde_json::json!({
+        "reminder_id": reminder_id.to_string(),
+        "task_id": task_id.to_string(),
+        "user_id": user_id.to_string(),
+        "title": reminder.title,
+        "due_date": reminder
+            .due_date
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+    }))
+}
+
+/// puts the reminder in the notification inbox of its author
+pub struct InAppDelivery {
+    pool: sqlx::MySqlPool,
+}
+impl InAppDelivery {
+    pub fn new(pool: sqlx::MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+#[async_trait::async_trait]
+impl Delivery for InAppDelivery {
+    fn channel(&self) -> ReminderChannel {
+        ReminderChannel::InApp
+    }
+
+    async fn deliver(&self, reminder: &DueReminder) -> anyhow::Result<()> {
+        let user_id = binary_to_ulid(reminder.reminder.author_id.as_slice())?;
+        let task_id = binary_to_ulid(reminder.reminder.todo_id.as_slice())?;
+
+        model::notifications::enqueue_notification(
+            &self.pool,
+            user_id,
+            NotificationKind::Reminder,
+            Some(task_id),
+            &format!("Reminder: {}", reminder.title),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// the `http://` or `https://` URL of a webhook, refusing control characters and hosts that
+/// are obviously not public
+///
+/// hostnames are only resolved, and checked again, when the webhook is sent
+pub fn parse_webhook_url(url: &str) -> anyhow::Result<url::Url> {
+    // `Url::parse` silently drops tabs and newlines, so they are refused beforehand
+    if url.chars().any(char::is_control) {
+        anyhow::bail!("Control characters in the URL");
+    }
+    let url = url::Url::parse(url)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("Only http:// and https:// webhooks are supported");
+    }
+    match url.host() {
+        Some(url::Host::Ipv4(ip)) => check_public_ip(IpAddr::V4(ip))?,
+        Some(url::Host::Ipv6(ip)) => check_public_ip(IpAddr::V6(ip))?,
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.');
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                anyhow::bail!("Webhook to a local host");
+            }
+        }
+        None => anyhow::bail!("Webhook URL without host"),
+    }
+
+    Ok(url)
+}
+
+/// the address of an email reminder
+pub fn parse_email_address(address: &str) -> anyhow::Result<Address> {
+    Ok(address.parse::<Address>()?)
+}
+
+/// refuses loopback, private, link-local and other special-purpose addresses, which webhooks
+/// could otherwise use to reach services behind the server
+fn check_public_ip(ip: IpAddr) -> anyhow::Result<()> {
+    fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+        let [a, b, ..] = ip.octets();
+        !(ip.is_unspecified()
+            || ip.is_loopback()
+            || ip.is_private()
+            || ip.is_link_local()
+            || ip.is_broadcast()
+            || ip.is_documentation()
+            || ip.is_multicast()
+            // "this network", shared address space, IETF protocol assignments,
+            // benchmarking and reserved
+            || a == 0
+            || (a == 100 && (64..128).contains(&b))
+            || (a == 192 && b == 0 && ip.octets()[2] == 0)
+            || (a == 198 && (18..20).contains(&b))
+            || a >= 240)
+    }
+    fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+        if let Some(ip) = ip.to_ipv4_mapped() {
+            return is_public_ipv4(ip);
+        }
+        let segments = ip.segments();
+        // NAT64 embeds an IPv4 address in the last 32 bits
+        if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+            let [a, b] = segments[6].to_be_bytes();
+            let [c, d] = segments[7].to_be_bytes();
+            return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+        }
+        !(ip.is_unspecified()
+            || ip.is_loopback()
+            || ip.is_multicast()
+            // unique local, link-local, site-local and documentation
+            || (segments[0] & 0xfe00) == 0xfc00
+            || (segments[0] & 0xffc0) == 0xfe80
+            || (segments[0] & 0xffc0) == 0xfec0
+            || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+            // IPv4-compatible, deprecated
+            || segments[..6] == [0, 0, 0, 0, 0, 0])
+    }
+
+    let is_public = match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    };
+    if !is_public {
+        anyhow::bail!("Webhook to a non-public address: {}", ip);
+    }
+
+    Ok(())
+}
+
+/// POSTs the reminder as JSON to the URL in its target, expecting a 2xx response
+///
+/// the host is resolved once and every address checked with [`check_public_ip`], then the
+/// request is pinned to a checked address and never follows redirects
+pub struct WebhookDelivery {
+    timeout: Duration,
+}
+impl WebhookDelivery {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    async fn post(&self, url: &str, body: String) -> anyhow::Result<()> {
+        let url = parse_webhook_url(url)?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("Webhook URL without host"))?
+            .to_string();
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| anyhow::anyhow!("Webhook URL without port"))?;
+
+        let addresses = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+            .await?
+            .collect::<Vec<SocketAddr>>();
+        for address in addresses.iter() {
+            check_public_ip(address.ip())?;
+        }
+        let address = addresses
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Webhook host not found: {}", host))?;
+
+        let client = reqwest::Client::builder()
+            .resolve(&host, address)
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy()
+            .timeout(self.timeout)
+            .build()?;
+        let response = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Webhook responded with {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+#[async_trait::async_trait]
+impl Delivery for WebhookDelivery {
+    fn channel(&self) -> ReminderChannel {
+        ReminderChannel::Webhook
+    }
+
+    async fn deliver(&self, reminder: &DueReminder) -> anyhow::Result<()> {
+        let url = reminder
+            .reminder
+            .target
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Webhook reminder without URL"))?;
+        let body = payload(reminder)?.to_string();
+
+        tokio::time::timeout(self.timeout, self.post(url, body)).await?
+    }
+}
+
+/// sends the reminder to the address in its target through an SMTP relay, upgrading the
+/// connection with STARTTLS
+pub struct EmailDelivery {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    timeout: Duration,
+}
+impl EmailDelivery {
+    pub fn new(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        from: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+            .port(port)
+            .timeout(Some(timeout));
+        if let Some((username, password)) = credentials {
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: transport.build(),
+            from: Mailbox::new(None, parse_email_address(from)?),
+            timeout,
+        })
+    }
+
+    async fn send(&self, to: &str, subject: &str, body: String) -> anyhow::Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(Mailbox::new(None, parse_email_address(to)?))
+            .subject(subject)
+            .body(body)?;
+
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
+#[async_trait::async_trait]
+impl Delivery for EmailDelivery {
+    fn channel(&self) -> ReminderChannel {
+        ReminderChannel::Email
+    }
+
+    async fn deliver(&self, reminder: &DueReminder) -> anyhow::Result<()> {
+        let to = reminder
+            .reminder
+            .target
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Email reminder without address"))?;
+        let title = reminder.title.replace(['\r', '\n'], " ");
+        let subject = format!("Reminder: {}", title);
+        let body = match reminder.due_date {
+            Some(due_date) => format!("{}\nDue: {}\n", title, due_date.format("%Y-%m-%d %H:%M:%S")),
+            None => format!("{}\n", title),
+        };
+
+        tokio::time::timeout(self.timeout, self.send(to, &subject, body)).await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_url_accepts_public_http_and_https() {
+        assert!(parse_webhook_url("http://example.com/hook").is_ok());
+        assert!(parse_webhook_url("https://example.com:8443/hook?a=1").is_ok());
+        assert!(parse_webhook_url("https://93.184.216.34/hook").is_ok());
+    }
+
+    #[test]
+    fn webhook_url_refuses_control_characters() {
+        assert!(parse_webhook_url("http://example.com/\r\nX-Injected: 1").is_err());
+        assert!(parse_webhook_url("http://example.com/\thook").is_err());
+        assert!(parse_webhook_url("http://example.com/hook\n").is_err());
+    }
+
+    #[test]
+    fn webhook_url_refuses_other_schemes() {
+        assert!(parse_webhook_url("ftp://example.com/").is_err());
+        assert!(parse_webhook_url("file:///etc/passwd").is_err());
+        assert!(parse_webhook_url("example.com/hook").is_err());
+    }
+
+    #[test]
+    fn webhook_url_refuses_local_hosts() {
+        for url in [
+            "http://localhost/",
+            "http://api.localhost./",
+            "http://127.0.0.1/",
+            "http://10.1.2.3/",
+            "http://172.16.0.1/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/",
+            "http://0.0.0.0/",
+            "http://[::1]/",
+            "http://[fe80::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[64:ff9b::a9fe:a9fe]/",
+        ] {
+            assert!(parse_webhook_url(url).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn public_ips() {
+        assert!(check_public_ip("8.8.8.8".parse().unwrap()).is_ok());
+        assert!(check_public_ip("2606:4700:4700::1111".parse().unwrap()).is_ok());
+        assert!(check_public_ip("198.18.0.1".parse().unwrap()).is_err());
+        assert!(check_public_ip("255.255.255.255".parse().unwrap()).is_err());
+        assert!(check_public_ip("2001:db8::1".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn email_addresses() {
+        assert!(parse_email_address("someone@example.com").is_ok());
+        assert!(parse_email_address("someone").is_err());
+        assert!(parse_email_address("@example.com").is_err());
+        assert!(parse_email_address("someone@example.com>\r\nRCPT TO:<x@y.z").is_err());
+    }
+}
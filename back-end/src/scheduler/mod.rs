@@ -0,0 +1,176 @@
+mod delivery;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+pub use delivery::{
+    parse_email_address, parse_webhook_url, Delivery, EmailDelivery, InAppDelivery, WebhookDelivery,
+};
+
+use crate::{
+    model::{
+        self,
+        types::{NotificationKind, ReminderChannel},
+    },
+    utils::binary_to_ulid,
+};
+
+/// fires due reminders in the background through the delivery registered for their channel,
+/// notifies authors of tasks that became overdue, archives done tasks following the auto-archive
+/// rule of their author, empties the trash past its retention window and forgets expired
+/// idempotency keys, workspace invitations and share links
+///
+/// reminders are leased in the database before delivery and only marked as fired afterwards,
+/// so a reminder interrupted by a crash or a restart is delivered again: at least once
+pub struct Scheduler {
+    pool: sqlx::MySqlPool,
+    deliveries: HashMap<ReminderChannel, Arc<dyn Delivery>>,
+    interval: Duration,
+    trash_retention: Duration,
+}
+impl Scheduler {
+    const BATCH_SIZE: usize = 100;
+    const MAX_ATTEMPTS: i32 = 10;
+    const LEASE_SECS: i64 = 300;
+    const MAX_RETRY_AFTER_SECS: i64 = 3600;
+    const DEFAULT_TRASH_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+    pub fn new(pool: sqlx::MySqlPool, interval: Duration) -> Self {
+        Self {
+            pool,
+            deliveries: HashMap::new(),
+            interval,
+            trash_retention: Self::DEFAULT_TRASH_RETENTION,
+        }
+    }
+
+    /// how long tasks stay in the trash before being deleted permanently, 30 days by default
+    pub fn with_trash_retention(mut self, trash_retention: Duration) -> Self {
+        self.trash_retention = trash_retention;
+        self
+    }
+
+    pub fn with_delivery(mut self, delivery: impl Delivery + 'static) -> Self {
+        self.deliveries
+            .insert(delivery.channel(), Arc::new(delivery));
+        self
+    }
+
+    /// runs the scheduler on the current actix runtime until the server stops
+    pub fn spawn(self) {
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                match self.run_once().await {
+                    Ok(0) => (),
+                    Ok(fired) => log::info!("fired {} reminders", fired),
+                    Err(e) => log::error!("failed to fire reminders: {}", e),
+                }
+                match self.notify_overdue().await {
+                    Ok(0) => (),
+                    Ok(notified) => log::info!("notified {} overdue tasks", notified),
+                    Err(e) => log::error!("failed to notify overdue tasks: {}", e),
+                }
+                match model::tasks::auto_archive_tasks(&self.pool).await {
+                    Ok(0) => (),
+                    Ok(archived) => log::info!("archived {} done tasks", archived),
+                    Err(e) => log::error!("failed to archive done tasks: {}", e),
+                }
+                match model::tasks::purge_expired_trash(
+                    &self.pool,
+                    self.trash_retention.as_secs() as i64,
+                )
+                .await
+                {
+                    Ok(0) => (),
+                    Ok(purged) => log::info!("purged {} tasks from the trash", purged),
+                    Err(e) => log::error!("failed to purge the trash: {}", e),
+                }
+                match model::idempotency::purge_expired_idempotency_keys(&self.pool).await {
+                    Ok(0) => (),
+                    Ok(purged) => log::info!("forgot {} idempotency keys", purged),
+                    Err(e) => log::error!("failed to forget idempotency keys: {}", e),
+                }
+                match model::workspaces::purge_expired_invitations(&self.pool).await {
+                    Ok(0) => (),
+                    Ok(purged) => log::info!("forgot {} workspace invitations", purged),
+                    Err(e) => log::error!("failed to forget workspace invitations: {}", e),
+                }
+                match model::share_links::purge_expired_share_links(&self.pool).await {
+                    Ok(0) => (),
+                    Ok(purged) => log::info!("forgot {} share links", purged),
+                    Err(e) => log::error!("failed to forget share links: {}", e),
+                }
+            }
+        });
+    }
+
+    /// delivers one batch of due reminders, returning how many were delivered
+    async fn run_once(&self) -> anyhow::Result<usize> {
+        let reminders = model::reminders::claim_due_reminders(
+            &self.pool,
+            Self::BATCH_SIZE,
+            Self::MAX_ATTEMPTS,
+            Self::LEASE_SECS,
+        )
+        .await?;
+
+        let mut fired = 0;
+        for reminder in reminders {
+            let id = reminder.reminder.id.clone();
+            let result = match self.deliveries.get(&reminder.reminder.channel) {
+                Some(delivery) => delivery.deliver(&reminder).await,
+                None => Err(anyhow::anyhow!(
+                    "No delivery for {}",
+                    reminder.reminder.channel
+                )),
+            };
+
+            match result {
+                Ok(()) => {
+                    model::reminders::mark_reminder_fired(&self.pool, &id).await?;
+                    fired += 1;
+                }
+                Err(e) => {
+                    // `attempts` was read before this attempt was counted
+                    let retry_after_secs = (30_i64 << (reminder.reminder.attempts.clamp(0, 7)))
+                        .min(Self::MAX_RETRY_AFTER_SECS);
+                    log::warn!("failed to deliver a reminder: {}", e);
+                    model::reminders::mark_reminder_failed(
+                        &self.pool,
+                        &id,
+                        &e.to_string(),
+                        retry_after_secs,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(fired)
+    }
+
+    /// notifies the author of each task that became overdue once per due date,
+    /// returning how many were notified
+    async fn notify_overdue(&self) -> anyhow::Result<usize> {
+        let tasks =
+            model::notifications::get_unnotified_overdue_tasks(&self.pool, Self::BATCH_SIZE)
+                .await?;
+
+        for task in tasks.iter() {
+            let Some(author_id) = task.author_id.as_deref() else {
+                continue;
+            };
+            model::notifications::enqueue_notification(
+                &self.pool,
+                binary_to_ulid(author_id)?,
+                NotificationKind::Overdue,
+                Some(binary_to_ulid(task.id.as_slice())?),
+                &format!("Overdue: {}", task.title),
+            )
+            .await?;
+        }
+
+        Ok(tasks.len())
+    }
+}
@@ -17,6 +17,12 @@ pub fn ulid_to_binary(ulid: Ulid) -> [u8; ULID_BIN_LEN] {
     u128::from(ulid).to_be_bytes()
 }
 
+/// random URL-safe token, e.g. for invitation links
+pub fn generate_token() -> String {
+    let bytes = rand::random::<[u8; 32]>();
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
 pub async fn check_is_logged_in(
     session: actix_session::Session,
     conn: impl sqlx::Acquire<'_, Database = sqlx::MySql>,